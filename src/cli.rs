@@ -0,0 +1,99 @@
+use std::io;
+
+use chrono::Utc;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+
+use crate::entities::sea_orm_active_enums::Role;
+use crate::entities::user::{self, ActiveModel as UserActiveModel, Entity as User};
+use crate::utils::balances::reconcile;
+
+const USAGE: &str =
+    "Usage: Spenser [reconcile [--repair] [--workspace <id>] | set-role <email> <user|admin>]";
+
+/// Runs an offline command when one is given on the command line.
+/// Returns `Ok(false)` when no command was given and the server should start.
+pub async fn run(db: &DatabaseConnection, args: &[String]) -> io::Result<bool> {
+    match args.first().map(String::as_str) {
+        None => Ok(false),
+        Some("reconcile") => {
+            reconcile_command(db, &args[1..]).await?;
+            Ok(true)
+        }
+        Some("set-role") => {
+            set_role_command(db, &args[1..]).await?;
+            Ok(true)
+        }
+        Some(_) => Err(io::Error::other(USAGE)),
+    }
+}
+
+async fn reconcile_command(db: &DatabaseConnection, args: &[String]) -> io::Result<()> {
+    let mut repair = false;
+    let mut workspace_id = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--repair" => repair = true,
+            "--workspace" => {
+                let id = args
+                    .next()
+                    .and_then(|id| id.parse::<i32>().ok())
+                    .ok_or_else(|| io::Error::other(USAGE))?;
+                workspace_id = Some(id);
+            }
+            _ => return Err(io::Error::other(USAGE)),
+        }
+    }
+
+    let report = reconcile(db, workspace_id, repair)
+        .await
+        .map_err(|e| io::Error::other(format!("❌ Reconciliation failed: {}", e)))?;
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).map_err(io::Error::other)?
+    );
+    if report.discrepancies.is_empty() {
+        println!("✅ Balances match the transaction ledger");
+    } else if report.repaired {
+        println!("✅ Repaired {} balance(s)", report.discrepancies.len());
+    } else {
+        println!(
+            "❌ Found {} balance discrepancy(ies); rerun with --repair to fix",
+            report.discrepancies.len()
+        );
+    }
+    Ok(())
+}
+
+async fn set_role_command(db: &DatabaseConnection, args: &[String]) -> io::Result<()> {
+    let [email, role] = args else {
+        return Err(io::Error::other(USAGE));
+    };
+    let role = match role.as_str() {
+        "user" => Role::User,
+        "admin" => Role::Admin,
+        _ => return Err(io::Error::other(USAGE)),
+    };
+
+    let user = User::find()
+        .filter(user::Column::Email.eq(email))
+        .one(db)
+        .await
+        .map_err(|e| io::Error::other(format!("❌ Database error: {}", e)))?
+        .ok_or_else(|| io::Error::other(format!("❌ No user with email {}", email)))?;
+
+    User::update(UserActiveModel {
+        id: Set(user.id),
+        role: Set(role),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    })
+    .exec(db)
+    .await
+    .map_err(|e| io::Error::other(format!("❌ Database error: {}", e)))?;
+
+    println!("✅ {} is now {:?}", email, role);
+    Ok(())
+}
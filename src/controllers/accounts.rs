@@ -0,0 +1,282 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, put, web};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    AppState,
+    controllers::transactions::TransactionResponse,
+    entities::account::{
+        ActiveModel as AccountActiveModel, Entity as Account, Model as AccountModel,
+    },
+    entities::sea_orm_active_enums::AccountKind,
+    entities::transaction::Entity as Transaction,
+    utils::auth::get_workspace_access,
+    utils::exchange_rates::validate_currency,
+    utils::ledger::lock_workspace,
+    utils::money::Money,
+    utils::validation::validate_name,
+    utils::workspaces::{WorkspaceAccess, base_currency},
+};
+
+#[derive(Deserialize, Debug)]
+pub struct CreateAccountRequest {
+    pub name: String,
+    pub kind: AccountKind,
+    pub currency: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UpdateAccountRequest {
+    pub name: String,
+    pub kind: AccountKind,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AccountResponse {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub user_id: Option<i32>,
+    pub name: String,
+    pub kind: AccountKind,
+    pub currency: String,
+    pub balance: Money,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<AccountModel> for AccountResponse {
+    fn from(account: AccountModel) -> Self {
+        Self {
+            id: account.id,
+            workspace_id: account.workspace_id,
+            user_id: account.user_id,
+            name: account.name,
+            kind: account.kind,
+            balance: Money::new(account.balance, account.currency.clone()),
+            currency: account.currency,
+            created_at: account.created_at,
+            updated_at: account.updated_at,
+        }
+    }
+}
+
+#[get("")]
+pub async fn get_all_accounts(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = whole_workspace(&req)?;
+
+    let accounts = Account::find()
+        .filter(crate::entities::account::Column::WorkspaceId.eq(access.workspace_id))
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let accounts: Vec<AccountResponse> = accounts.into_iter().map(AccountResponse::from).collect();
+    Ok(HttpResponse::Ok().json(accounts))
+}
+
+#[post("")]
+pub async fn create_account(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<CreateAccountRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = whole_workspace(&req)?;
+    let name = validate_name(&data.name)?;
+    let currency = validate_currency(&data.currency)?;
+
+    let account = AccountActiveModel {
+        workspace_id: Set(access.workspace_id),
+        user_id: Set(Some(access.user_id)),
+        name: Set(name.to_string()),
+        kind: Set(data.kind),
+        currency: Set(currency),
+        balance: Set(0),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(HttpResponse::Created().json(AccountResponse::from(account)))
+}
+
+#[get("/{id}")]
+pub async fn show_account(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = whole_workspace(&req)?;
+
+    let account = find_account(&state.db, &access, *id).await?;
+    Ok(HttpResponse::Ok().json(AccountResponse::from(account)))
+}
+
+/// Renames an account or changes its kind. The currency is fixed, because the
+/// balance is kept in it. Only credit cards may hold a negative balance, so a
+/// card in debt cannot become another kind.
+#[put("/{id}")]
+pub async fn update_account(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    data: web::Json<UpdateAccountRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = whole_workspace(&req)?;
+    let name = validate_name(&data.name)?;
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    let account = lock_account(&txn, &access, *id).await?;
+
+    if data.kind != AccountKind::CreditCard && account.balance < 0 {
+        return Err(actix_web::error::ErrorConflict(json!({
+            "status": "error",
+            "message": "Only credit card accounts may have a negative balance"
+        })));
+    }
+
+    let mut updated_account: AccountActiveModel = account.into();
+    updated_account.name = Set(name.to_string());
+    updated_account.kind = Set(data.kind);
+    updated_account.updated_at = Set(Utc::now());
+
+    let updated = updated_account.update(&txn).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(AccountResponse::from(updated)))
+}
+
+/// Deletes an account that has no transactions.
+#[delete("/{id}")]
+pub async fn delete_account(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = whole_workspace(&req)?;
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    // New transactions are inserted only while the workspace is locked, so
+    // none can slip in between the count and the delete, whose cascade would
+    // take them along
+    let account = lock_account(&txn, &access, *id).await?;
+
+    let transactions = Transaction::find()
+        .filter(crate::entities::transaction::Column::AccountId.eq(account.id))
+        .count(&txn)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if transactions > 0 {
+        return Err(actix_web::error::ErrorConflict(json!({
+            "status": "error",
+            "message": "Delete or move the account's transactions first"
+        })));
+    }
+
+    Account::delete_by_id(account.id)
+        .exec(&txn)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+#[get("/{id}/transactions")]
+pub async fn get_account_transactions(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = whole_workspace(&req)?;
+    let account = find_account(&state.db, &access, *id).await?;
+    let base_currency = base_currency(&state.db, access.workspace_id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let transactions: Vec<TransactionResponse> = Transaction::find()
+        .filter(crate::entities::transaction::Column::AccountId.eq(account.id))
+        .all(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .into_iter()
+        .map(|transaction| TransactionResponse::new(transaction, &base_currency))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(transactions))
+}
+
+// Helpers
+
+/// Account balances span every category, so grants limited to some of them
+/// cannot see accounts.
+fn whole_workspace(req: &HttpRequest) -> Result<WorkspaceAccess, actix_web::Error> {
+    let access = get_workspace_access(req);
+    access.require_whole_workspace("Accounts need access to the whole workspace")?;
+    Ok(access)
+}
+
+async fn find_account(
+    db: &DatabaseConnection,
+    access: &WorkspaceAccess,
+    account_id: i32,
+) -> Result<AccountModel, actix_web::Error> {
+    Account::find_by_id(account_id)
+        .filter(crate::entities::account::Column::WorkspaceId.eq(access.workspace_id))
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(
+                json!({"status": "error", "message": "Account not found or unauthorized"}),
+            )
+        })
+}
+
+/// Loads an account with `SELECT ... FOR UPDATE`, so balance changes wait
+/// for the surrounding database transaction to end. The workspace is locked
+/// first, in the order `utils::ledger` sets out.
+async fn lock_account<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
+    account_id: i32,
+) -> Result<AccountModel, actix_web::Error> {
+    lock_workspace(db, access).await?;
+    Account::find_by_id(account_id)
+        .filter(crate::entities::account::Column::WorkspaceId.eq(access.workspace_id))
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(
+                json!({"status": "error", "message": "Account not found or unauthorized"}),
+            )
+        })
+}
@@ -0,0 +1,181 @@
+use actix_web::{HttpRequest, HttpResponse, get, post, put, web};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+use serde_json::json;
+
+use crate::{
+    AppState,
+    controllers::me::LOGIN_HISTORY_LIMIT,
+    entities::account::Entity as Account,
+    entities::category::Entity as Category,
+    entities::login_attempt::Entity as LoginAttempt,
+    entities::session::Entity as Session,
+    entities::transaction::Entity as Transaction,
+    entities::user::{ActiveModel as UserActiveModel, Entity as User, Model as UserModel},
+    entities::workspace::Entity as Workspace,
+    utils::auth::get_user_by_id,
+    utils::balances::reconcile,
+    utils::login_attempts::unlock,
+    utils::sessions::revoke_user_sessions,
+};
+
+#[get("/users")]
+pub async fn get_all_users(state: web::Data<AppState>) -> Result<HttpResponse, actix_web::Error> {
+    let users = User::find()
+        .order_by_asc(crate::entities::user::Column::Id)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(users))
+}
+
+#[put("/users/{id}/disable")]
+pub async fn disable_user(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    if get_user_by_id(&req) == *id {
+        return Err(actix_web::error::ErrorUnprocessableEntity(
+            json!({"status": "error", "message": "You cannot disable your own account"}),
+        ));
+    }
+
+    let user = find_user(&state.db, *id).await?;
+
+    let mut disabled_user: UserActiveModel = user.into();
+    disabled_user.disabled_at = Set(Some(Utc::now()));
+    disabled_user.updated_at = Set(Utc::now());
+    let disabled = disabled_user.update(&state.db).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    revoke_user_sessions(&state.db, disabled.id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(disabled))
+}
+
+#[put("/users/{id}/enable")]
+pub async fn enable_user(
+    state: web::Data<AppState>,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = find_user(&state.db, *id).await?;
+
+    let mut enabled_user: UserActiveModel = user.into();
+    enabled_user.disabled_at = Set(None);
+    enabled_user.updated_at = Set(Utc::now());
+    let enabled = enabled_user.update(&state.db).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(enabled))
+}
+
+#[put("/users/{id}/unlock")]
+pub async fn unlock_user(
+    state: web::Data<AppState>,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = find_user(&state.db, *id).await?;
+
+    unlock(&state.db, user.id).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+#[get("/users/{id}/login-attempts")]
+pub async fn get_user_login_attempts(
+    state: web::Data<AppState>,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = find_user(&state.db, *id).await?;
+
+    let attempts = LoginAttempt::find()
+        .filter(crate::entities::login_attempt::Column::UserId.eq(user.id))
+        .order_by_desc(crate::entities::login_attempt::Column::CreatedAt)
+        .limit(LOGIN_HISTORY_LIMIT)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(attempts))
+}
+
+#[get("/stats")]
+pub async fn get_stats(state: web::Data<AppState>) -> Result<HttpResponse, actix_web::Error> {
+    let db = &state.db;
+    let map_err = |e: sea_orm::DbErr| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    };
+
+    let users = User::find().count(db).await.map_err(map_err)?;
+    let disabled_users = User::find()
+        .filter(crate::entities::user::Column::DisabledAt.is_not_null())
+        .count(db)
+        .await
+        .map_err(map_err)?;
+    let active_sessions = Session::find()
+        .filter(crate::entities::session::Column::RevokedAt.is_null())
+        .count(db)
+        .await
+        .map_err(map_err)?;
+    let workspaces = Workspace::find().count(db).await.map_err(map_err)?;
+    let accounts = Account::find().count(db).await.map_err(map_err)?;
+    let categories = Category::find().count(db).await.map_err(map_err)?;
+    let transactions = Transaction::find().count(db).await.map_err(map_err)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "users": users,
+        "disabled_users": disabled_users,
+        "active_sessions": active_sessions,
+        "workspaces": workspaces,
+        "accounts": accounts,
+        "categories": categories,
+        "transactions": transactions
+    })))
+}
+
+#[get("/balances/reconcile")]
+pub async fn get_reconciliation(
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let report = reconcile(&state.db, None, false).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[post("/balances/reconcile")]
+pub async fn repair_balances(state: web::Data<AppState>) -> Result<HttpResponse, actix_web::Error> {
+    let report = reconcile(&state.db, None, true).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+async fn find_user(db: &DatabaseConnection, user_id: i32) -> Result<UserModel, actix_web::Error> {
+    User::find_by_id(user_id)
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(json!({"status": "error", "message": "User not found"}))
+        })
+}
@@ -1,19 +1,36 @@
-use actix_web::{HttpResponse, Responder, post, web};
-use argon2::{
-    Argon2, PasswordHasher, PasswordVerifier,
-    password_hash::PasswordHash,
-    password_hash::{SaltString, rand_core::OsRng},
-};
+use actix_web::{HttpRequest, HttpResponse, Responder, post, web};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{EncodingKey, Header, encode};
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::utils::responses::unauthorized;
+use crate::entities::sea_orm_active_enums::{Role, TokenPurpose};
+use crate::mailer::Email;
+use crate::utils::auth::{ClientInfo, client_info};
+use crate::utils::cookies::{REFRESH_COOKIE, check_csrf, wants_cookies};
+use crate::utils::jwt::{issue_challenge_token, validate_challenge_token};
+use crate::utils::login_attempts::{
+    check_ip, check_not_locked, record_failure, record_success, throttle,
+};
+use crate::utils::one_time_tokens::{consume_one_time_token, issue_one_time_token};
+use crate::utils::password::{hash_password, needs_rehash, verify_password};
+use crate::utils::responses::{forbidden, unauthorized};
+use crate::utils::sessions::{
+    IssuedTokens, revoke_session_by_refresh_token, revoke_user_sessions, rotate_refresh_token,
+    start_session,
+};
+use crate::utils::totp::verify_second_factor;
+use crate::utils::validation::validate_email;
+use crate::utils::workspaces::{
+    DEFAULT_BASE_CURRENCY, PERSONAL_WORKSPACE_NAME, create_owned_workspace,
+};
 use crate::{
     AppState,
-    entities::user::{ActiveModel as UserActiveModel, Entity as User},
+    entities::one_time_token::{self, Entity as OneTimeToken},
+    entities::user::{ActiveModel as UserActiveModel, Entity as User, Model as UserModel},
 };
 
 #[derive(Deserialize, Debug)]
@@ -22,13 +39,64 @@ pub struct SignInRequest {
     pub password: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Claims {
     pub sub: i32,
-    pub role: String,
+    pub role: Role,
     pub exp: i64,
+    pub sid: String,
+    pub jti: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TotpSignInRequest {
+    pub challenge_token: String,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct VerifyEmailRequest {
+    pub token: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct ResendVerificationRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MagicLinkRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RedeemMagicLinkRequest {
+    pub token: String,
+}
+
+const PASSWORD_RESET_LIFETIME: Duration = Duration::hours(1);
+const EMAIL_VERIFICATION_LIFETIME: Duration = Duration::hours(24);
+const MAGIC_LINK_LIFETIME: Duration = Duration::minutes(15);
+/// Minimum time between two magic links for the same account.
+const MAGIC_LINK_COOLDOWN: Duration = Duration::minutes(1);
+
 #[derive(Deserialize, Debug)]
 pub struct SignUpRequest {
     pub email: String,
@@ -42,6 +110,8 @@ pub async fn sign_up(
     state: web::Data<AppState>,
     data: web::Json<SignUpRequest>,
 ) -> Result<impl Responder, actix_web::Error> {
+    validate_email(&data.email)?;
+
     // Check if email exists
     let db = &state.db;
     if User::find()
@@ -58,11 +128,8 @@ pub async fn sign_up(
     }
 
     // Hash password
-    let salt = SaltString::generate(&mut OsRng);
-    let hashed_password = Argon2::default()
-        .hash_password(data.password.as_bytes(), &salt)
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Hash error: {}", e)))?
-        .to_string();
+    state.password_policy.validate(&data.password)?;
+    let hashed_password = hash_password(&state.password_policy, &data.password)?;
 
     // Create user
     let new_user = UserActiveModel {
@@ -70,14 +137,30 @@ pub async fn sign_up(
         password: Set(hashed_password),
         first_name: Set(data.firstname.clone()),
         last_name: Set(data.lastname.clone()),
-        balance: Set(0),
-        created_at: Set(Utc::now().into()),
-        updated_at: Set(Utc::now().into()),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+        verified_at: Set(None),
         ..Default::default() // id auto-increments
     };
-    new_user.insert(db).await.map_err(|e| {
+    let txn = db.begin().await.map_err(|e| {
         actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
     })?;
+    let user = new_user.insert(&txn).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    create_owned_workspace(
+        &txn,
+        user.id,
+        PERSONAL_WORKSPACE_NAME,
+        DEFAULT_BASE_CURRENCY,
+    )
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    send_verification_email(&state, &user).await?;
 
     Ok(HttpResponse::Created().json(json!({
         "status": "success",
@@ -88,42 +171,562 @@ pub async fn sign_up(
 #[post("/sign-in")]
 pub async fn sign_in(
     state: web::Data<AppState>,
+    req: HttpRequest,
     data: web::Json<SignInRequest>,
 ) -> Result<impl Responder, actix_web::Error> {
+    let client = client_info(&req);
+    let ip = &client.ip;
+    let policy = &state.login_policy;
+
+    // Throttle before touching the credentials
+    check_ip(&state.db, policy, ip).await?;
+    throttle(&state.db, policy, &data.email).await?;
+
     // Find user by email
     let user = User::find()
         .filter(crate::entities::user::Column::Email.eq(&data.email))
         .one(&state.db)
         .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
-        .ok_or_else(|| unauthorized("Invalid email or password"))?;
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    let Some(user) = user else {
+        record_failure(&state.db, policy, &data.email, ip, None)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+        return Err(unauthorized("Invalid email or password"));
+    };
+
+    check_not_locked(&user)?;
 
     // Verify password
-    let parsed_hash = PasswordHash::new(&user.password)
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Hash error: {}", e)))?;
-    Argon2::default()
-        .verify_password(data.password.as_bytes(), &parsed_hash)
-        .map_err(|_| unauthorized("Invalid email or password"))?;
-
-    // Generate JWT
-    let claims = Claims {
-        sub: user.id,
-        role: "user".to_string(),
-        exp: (Utc::now() + Duration::hours(4)).timestamp(), // 4-hour expiration
-    };
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(
-            std::env::var("JWT_SECRET")
-                .map_err(|_| actix_web::error::ErrorInternalServerError("JWT_SECRET not set"))?
-                .as_bytes(),
-        ),
+    if !verify_password(&data.password, &user.password)? {
+        record_failure(&state.db, policy, &data.email, ip, Some(&user))
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+        return Err(unauthorized("Invalid email or password"));
+    }
+
+    if user.disabled_at.is_some() {
+        return Err(forbidden("Account is disabled"));
+    }
+
+    // Upgrade hashes made with older Argon2 parameters while the plain
+    // password is at hand
+    if needs_rehash(&state.password_policy, &user.password) {
+        UserActiveModel {
+            id: Set(user.id),
+            password: Set(hash_password(&state.password_policy, &data.password)?),
+            ..Default::default()
+        }
+        .update(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    }
+
+    let cookie_mode = wants_cookies(state.cookies.as_ref(), &req);
+    complete_sign_in(&state, &user, &client, cookie_mode).await
+}
+
+#[post("/sign-in/totp")]
+pub async fn sign_in_totp(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<TotpSignInRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let client = client_info(&req);
+    let ip = &client.ip;
+    let policy = &state.login_policy;
+    let claims = validate_challenge_token(&data.challenge_token, &state.jwt_keys)?;
+
+    check_ip(&state.db, policy, ip).await?;
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let user = User::find_by_id(claims.sub)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| unauthorized("Invalid or expired challenge token"))?;
+
+    if user.disabled_at.is_some() {
+        return Err(forbidden("Account is disabled"));
+    }
+    check_not_locked(&user)?;
+
+    if user.totp_enabled_at.is_none()
+        || !verify_second_factor(
+            &txn,
+            &user,
+            data.code.as_deref(),
+            data.recovery_code.as_deref(),
+        )
+        .await?
+    {
+        // Failed codes count towards the lockout like failed passwords
+        drop(txn);
+        record_failure(&state.db, policy, &user.email, ip, Some(&user))
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+        return Err(unauthorized("Invalid two-factor code"));
+    }
+
+    record_success(&txn, &user.email, ip, user.id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    let tokens = start_session(&txn, &state.jwt_keys, &user, &client).await?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let cookie_mode = wants_cookies(state.cookies.as_ref(), &req);
+    Ok(tokens_response(&state, cookie_mode, tokens))
+}
+
+#[post("/refresh")]
+pub async fn refresh(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: Option<web::Json<RefreshTokenRequest>>,
+) -> Result<impl Responder, actix_web::Error> {
+    let (refresh_token, from_cookie) = refresh_token_from(&state, &req, data)?;
+    let tokens = rotate_refresh_token(
+        &state.db,
+        &state.jwt_keys,
+        &refresh_token,
+        &client_info(&req),
     )
-    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("JWT error: {}", e)))?;
+    .await?;
+
+    let cookie_mode = from_cookie || wants_cookies(state.cookies.as_ref(), &req);
+    Ok(tokens_response(&state, cookie_mode, tokens))
+}
+
+#[post("/sign-out")]
+pub async fn sign_out(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: Option<web::Json<RefreshTokenRequest>>,
+) -> Result<impl Responder, actix_web::Error> {
+    let (refresh_token, from_cookie) = refresh_token_from(&state, &req, data)?;
+    revoke_session_by_refresh_token(&state.db, &refresh_token)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let mut response = HttpResponse::Ok();
+    if let Some(cookies) = state.cookies.as_ref().filter(|_| from_cookie) {
+        cookies.clear_auth_cookies(&mut response);
+    }
+    Ok(response.json(json!({"status": "success"})))
+}
+
+/// Takes the refresh token from the request body or, in cookie mode, from the
+/// refresh cookie. Cookie-sourced tokens need a matching CSRF header; the flag
+/// returned tells which source was used.
+fn refresh_token_from(
+    state: &AppState,
+    req: &HttpRequest,
+    data: Option<web::Json<RefreshTokenRequest>>,
+) -> Result<(String, bool), actix_web::Error> {
+    if let Some(data) = data {
+        return Ok((data.into_inner().refresh_token, false));
+    }
+
+    match req
+        .cookie(REFRESH_COOKIE)
+        .filter(|_| state.cookies.is_some())
+    {
+        Some(cookie) => {
+            check_csrf(req)?;
+            Ok((cookie.value().to_string(), true))
+        }
+        None => Err(unauthorized("Missing refresh token")),
+    }
+}
+
+#[post("/forgot-password")]
+pub async fn forgot_password(
+    state: web::Data<AppState>,
+    data: web::Json<ForgotPasswordRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user = User::find()
+        .filter(crate::entities::user::Column::Email.eq(&data.email))
+        .filter(crate::entities::user::Column::DisabledAt.is_null())
+        .one(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    // Respond identically whether or not the email exists: the token and
+    // the email are handled after the response so its timing and status
+    // don't reveal the account either.
+    if let Some(user) = user {
+        let state = state.clone();
+        actix_web::rt::spawn(async move {
+            let token = match issue_one_time_token(
+                &state.db,
+                user.id,
+                TokenPurpose::PasswordReset,
+                PASSWORD_RESET_LIFETIME,
+            )
+            .await
+            {
+                Ok(token) => token,
+                Err(e) => {
+                    eprintln!("❌ Failed to issue password reset token: {}", e);
+                    return;
+                }
+            };
+
+            let email = Email {
+                to: user.email,
+                subject: "Reset your Spenser password".to_string(),
+                body: format!(
+                    "Someone asked to reset the password of your Spenser account.\n\n\
+                     Use this link within the next hour to choose a new password:\n\
+                     {}/reset-password?token={}\n\n\
+                     If this wasn't you, you can ignore this message.",
+                    state.app_url, token
+                ),
+            };
+            if let Err(e) = state.mailer.send(&email).await {
+                eprintln!("❌ Failed to send password reset email: {}", e);
+            }
+        });
+    }
+
+    Ok(HttpResponse::Accepted().json(json!({
+        "status": "success",
+        "message": "If an account exists for this email, a reset link has been sent."
+    })))
+}
+
+#[post("/reset-password")]
+pub async fn reset_password(
+    state: web::Data<AppState>,
+    data: web::Json<ResetPasswordRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    // Validate first so a rejected password doesn't burn the token
+    state.password_policy.validate(&data.password)?;
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let token = consume_one_time_token(&txn, &data.token, TokenPurpose::PasswordReset)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorBadRequest(
+                json!({"status": "error", "message": "Invalid or expired reset token"}),
+            )
+        })?;
+
+    UserActiveModel {
+        id: Set(token.user_id),
+        password: Set(hash_password(&state.password_policy, &data.password)?),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .update(&txn)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    // Sign out everywhere: whoever knew the old password loses access
+    revoke_user_sessions(&txn, token.user_id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
 
     Ok(HttpResponse::Ok().json(json!({
         "status": "success",
-        "token": token
+        "message": "Password has been reset."
+    })))
+}
+
+#[post("/verify-email")]
+pub async fn verify_email(
+    state: web::Data<AppState>,
+    data: web::Json<VerifyEmailRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let token = consume_one_time_token(&txn, &data.token, TokenPurpose::EmailVerification)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorBadRequest(
+                json!({"status": "error", "message": "Invalid or expired verification token"}),
+            )
+        })?;
+
+    UserActiveModel {
+        id: Set(token.user_id),
+        verified_at: Set(Some(Utc::now())),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .update(&txn)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "Email address verified."
+    })))
+}
+
+#[post("/resend-verification")]
+pub async fn resend_verification(
+    state: web::Data<AppState>,
+    data: web::Json<ResendVerificationRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user = User::find()
+        .filter(crate::entities::user::Column::Email.eq(&data.email))
+        .filter(crate::entities::user::Column::VerifiedAt.is_null())
+        .filter(crate::entities::user::Column::DisabledAt.is_null())
+        .one(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    // Respond identically whether or not the email exists, sending the link
+    // after the response so its timing and status don't reveal the account
+    if let Some(user) = user {
+        let state = state.clone();
+        actix_web::rt::spawn(async move {
+            if let Err(e) = send_verification_email(&state, &user).await {
+                eprintln!("❌ Failed to issue verification token: {}", e);
+            }
+        });
+    }
+
+    Ok(HttpResponse::Accepted().json(json!({
+        "status": "success",
+        "message": "If an unverified account exists for this email, a new link has been sent."
+    })))
+}
+
+#[post("/magic-link")]
+pub async fn request_magic_link(
+    state: web::Data<AppState>,
+    data: web::Json<MagicLinkRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user = User::find()
+        .filter(crate::entities::user::Column::Email.eq(&data.email))
+        .filter(crate::entities::user::Column::DisabledAt.is_null())
+        .one(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    // Respond identically whether the email exists or the link was throttled.
+    // The link is issued and mailed after the response so its timing and
+    // status don't reveal the account either.
+    if let Some(user) = user {
+        let state = state.clone();
+        actix_web::rt::spawn(async move {
+            if let Err(e) = send_magic_link(&state, user).await {
+                eprintln!("❌ Failed to issue magic link: {}", e);
+            }
+        });
+    }
+
+    Ok(HttpResponse::Accepted().json(json!({
+        "status": "success",
+        "message": "If an account exists for this email, a sign-in link has been sent."
     })))
 }
+
+#[post("/magic-link/redeem")]
+pub async fn redeem_magic_link(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<RedeemMagicLinkRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let client = client_info(&req);
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let token = consume_one_time_token(&txn, &data.token, TokenPurpose::MagicLink)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| unauthorized("Invalid or expired sign-in link"))?;
+
+    let user = User::find_by_id(token.user_id)
+        .one(&txn)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| unauthorized("Invalid or expired sign-in link"))?;
+
+    if user.disabled_at.is_some() {
+        return Err(forbidden("Account is disabled"));
+    }
+    check_not_locked(&user)?;
+
+    // Following the link proves the address belongs to the user
+    let user = if user.verified_at.is_none() {
+        let mut verified: UserActiveModel = user.into();
+        verified.verified_at = Set(Some(Utc::now()));
+        verified.updated_at = Set(Utc::now());
+        verified.update(&txn).await.map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?
+    } else {
+        user
+    };
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let cookie_mode = wants_cookies(state.cookies.as_ref(), &req);
+    complete_sign_in(&state, &user, &client, cookie_mode).await
+}
+
+/// Issues a magic link for `user` and mails it, unless one was sent within
+/// `MAGIC_LINK_COOLDOWN`. Delivery failures are logged, not returned.
+async fn send_magic_link(state: &AppState, user: UserModel) -> Result<(), DbErr> {
+    let recently_sent = OneTimeToken::find()
+        .filter(one_time_token::Column::UserId.eq(user.id))
+        .filter(one_time_token::Column::Purpose.eq(TokenPurpose::MagicLink))
+        .filter(one_time_token::Column::CreatedAt.gt(Utc::now() - MAGIC_LINK_COOLDOWN))
+        .one(&state.db)
+        .await?
+        .is_some();
+    if recently_sent {
+        return Ok(());
+    }
+
+    let token = issue_one_time_token(
+        &state.db,
+        user.id,
+        TokenPurpose::MagicLink,
+        MAGIC_LINK_LIFETIME,
+    )
+    .await?;
+
+    let email = Email {
+        to: user.email,
+        subject: "Your Spenser sign-in link".to_string(),
+        body: format!(
+            "Use this link within the next 15 minutes to sign in to Spenser:\n\
+             {}/magic-link?token={}\n\n\
+             The link works once. If you didn't ask for it, you can ignore this message.",
+            state.app_url, token
+        ),
+    };
+    if let Err(e) = state.mailer.send(&email).await {
+        eprintln!("❌ Failed to send magic link email: {}", e);
+    }
+    Ok(())
+}
+
+/// Issues a fresh verification token for `user` and mails it to their
+/// current address. Delivery failures are logged, not returned.
+pub async fn send_verification_email(
+    state: &AppState,
+    user: &UserModel,
+) -> Result<(), actix_web::Error> {
+    let token = issue_one_time_token(
+        &state.db,
+        user.id,
+        TokenPurpose::EmailVerification,
+        EMAIL_VERIFICATION_LIFETIME,
+    )
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    let email = Email {
+        to: user.email.clone(),
+        subject: "Verify your Spenser email address".to_string(),
+        body: format!(
+            "Welcome to Spenser, {}!\n\n\
+             Confirm your email address within the next 24 hours with this link:\n\
+             {}/verify-email?token={}",
+            user.first_name, state.app_url, token
+        ),
+    };
+    if let Err(e) = state.mailer.send(&email).await {
+        eprintln!("❌ Failed to send verification email: {}", e);
+    }
+    Ok(())
+}
+
+/// Finishes a successful primary authentication of `user`. Users with
+/// two-factor authentication get a challenge token to exchange at
+/// `/auth/sign-in/totp`; everyone else gets a session right away.
+pub async fn complete_sign_in(
+    state: &AppState,
+    user: &UserModel,
+    client: &ClientInfo,
+    cookie_mode: bool,
+) -> Result<HttpResponse, actix_web::Error> {
+    if user.totp_enabled_at.is_some() {
+        let challenge_token = issue_challenge_token(&state.jwt_keys, user.id)?;
+        return Ok(HttpResponse::Ok().json(json!({
+            "status": "mfa_required",
+            "challenge_token": challenge_token
+        })));
+    }
+
+    record_success(&state.db, &user.email, &client.ip, user.id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    let tokens = start_session(&state.db, &state.jwt_keys, user, client).await?;
+
+    Ok(tokens_response(state, cookie_mode, tokens))
+}
+
+/// In cookie mode the tokens only travel in HttpOnly cookies and the body
+/// carries the CSRF token the client must echo back.
+fn tokens_response(state: &AppState, cookie_mode: bool, tokens: IssuedTokens) -> HttpResponse {
+    if let Some(cookies) = state.cookies.as_ref().filter(|_| cookie_mode) {
+        let mut response = HttpResponse::Ok();
+        let csrf_token = cookies.set_auth_cookies(&mut response, &tokens);
+        return response.json(json!({
+            "status": "success",
+            "csrf_token": csrf_token
+        }));
+    }
+
+    HttpResponse::Ok().json(json!({
+        "status": "success",
+        "token": tokens.access_token,
+        "refresh_token": tokens.refresh_token
+    }))
+}
@@ -0,0 +1,83 @@
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+
+use crate::{
+    AppState,
+    utils::{
+        auth::get_workspace_access,
+        balances::{cash_flow, reconcile, trial_balance},
+        workspaces::WorkspaceAccess,
+    },
+};
+
+/// Income and expense totals of the workspace, limited to the categories an
+/// access grant covers.
+#[get("/summary")]
+pub async fn get_summary(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = get_workspace_access(&req);
+
+    let flow = cash_flow(&state.db, access.workspace_id, access.category_ids)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(flow))
+}
+
+/// Debit and credit totals of every account and category, which always sum to
+/// zero in a consistent journal.
+#[get("/trial-balance")]
+pub async fn get_trial_balance(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let workspace_id = whole_workspace(&get_workspace_access(&req))?;
+
+    let report = trial_balance(&state.db, workspace_id).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[get("/reconcile")]
+pub async fn get_reconciliation(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let workspace_id = whole_workspace(&get_workspace_access(&req))?;
+
+    let report = reconcile(&state.db, Some(workspace_id), false)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[post("/reconcile")]
+pub async fn repair_balances(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let workspace_id = whole_workspace(&get_workspace_access(&req))?;
+
+    let report = reconcile(&state.db, Some(workspace_id), true)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Reconciliation and the trial balance cover every category, so grants
+/// limited to some of them cannot use them.
+fn whole_workspace(access: &WorkspaceAccess) -> Result<i32, actix_web::Error> {
+    access.require_whole_workspace("Balance reports need access to the whole workspace")?;
+    Ok(access.workspace_id)
+}
@@ -1,22 +1,34 @@
 use actix_web::{HttpRequest, HttpResponse, delete, get, post, put, web};
-use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait, sea_query::Query,
+};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::utils::auth::get_user_by_id;
+use crate::utils::auth::get_workspace_access;
+use crate::utils::exchange_rates::validate_currency;
+use crate::utils::ledger::{delete_legs, lock_workspace};
+use crate::utils::money::Money;
+use crate::utils::responses::forbidden;
+use crate::utils::workspaces::{WorkspaceAccess, base_currency};
 use crate::{
     AppState,
+    controllers::transactions::TransactionResponse,
     entities::category::{
         ActiveModel as CategoryActiveModel, Entity as Category, Model as CategoryModel,
     },
-    entities::transaction::Entity as Transaction,
+    entities::transaction::{self, Entity as Transaction},
+    entities::transfer::{self, Entity as Transfer},
+    entities::workspace::Entity as Workspace,
 };
 
 #[derive(Deserialize, Debug)]
 pub struct CreateCategoryRequest {
     pub name: String,
     pub description: Option<String>,
+    pub currency: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -25,21 +37,53 @@ pub struct UpdateCategoryRequest {
     pub description: Option<String>,
 }
 
+#[derive(Serialize, Debug)]
+pub struct CategoryResponse {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub user_id: Option<i32>,
+    pub name: String,
+    pub description: Option<String>,
+    pub balance: Money,
+    pub currency: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<CategoryModel> for CategoryResponse {
+    fn from(category: CategoryModel) -> Self {
+        Self {
+            id: category.id,
+            workspace_id: category.workspace_id,
+            user_id: category.user_id,
+            name: category.name,
+            description: category.description,
+            balance: Money::new(category.balance, category.currency.clone()),
+            currency: category.currency,
+            created_at: category.created_at,
+            updated_at: category.updated_at,
+        }
+    }
+}
+
 #[get("")]
 pub async fn get_all_categories(
     state: web::Data<AppState>,
     req: HttpRequest,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
+    let access = get_workspace_access(&req);
 
-    let categories = Category::find()
-        .filter(crate::entities::category::Column::UserId.eq(user_id))
-        .all(&state.db)
-        .await
-        .map_err(|e| {
-            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
-        })?;
+    let mut query = Category::find()
+        .filter(crate::entities::category::Column::WorkspaceId.eq(access.workspace_id));
+    if let Some(category_ids) = access.category_ids {
+        query = query.filter(crate::entities::category::Column::Id.is_in(category_ids));
+    }
+    let categories = query.all(&state.db).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
 
+    let categories: Vec<CategoryResponse> =
+        categories.into_iter().map(CategoryResponse::from).collect();
     Ok(HttpResponse::Ok().json(categories))
 }
 
@@ -49,15 +93,37 @@ pub async fn create_category(
     req: HttpRequest,
     data: web::Json<CreateCategoryRequest>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
+    let access = get_workspace_access(&req);
+    if access.category_ids.is_some() {
+        return Err(forbidden(
+            "Access grants limited to some categories cannot create new ones",
+        ));
+    }
+
+    // Categories default to the workspace's base currency
+    let currency = match &data.currency {
+        Some(currency) => validate_currency(currency)?,
+        None => {
+            Workspace::find_by_id(access.workspace_id)
+                .one(&state.db)
+                .await
+                .map_err(|e| {
+                    actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+                })?
+                .ok_or_else(|| actix_web::error::ErrorNotFound("Workspace not found"))?
+                .base_currency
+        }
+    };
 
     let new_category = CategoryActiveModel {
-        user_id: Set(user_id),
+        workspace_id: Set(access.workspace_id),
+        user_id: Set(Some(access.user_id)),
         name: Set(data.name.clone()),
         description: Set(data.description.clone()),
         balance: Set(0),
-        created_at: Set(Utc::now().into()),
-        updated_at: Set(Utc::now().into()),
+        currency: Set(currency),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
         ..Default::default()
     };
 
@@ -65,7 +131,7 @@ pub async fn create_category(
         actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
     })?;
 
-    Ok(HttpResponse::Ok().json(category))
+    Ok(HttpResponse::Ok().json(CategoryResponse::from(category)))
 }
 
 #[get("/{id}")]
@@ -74,10 +140,10 @@ pub async fn show_category(
     req: HttpRequest,
     id: web::Path<i32>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
+    let access = get_workspace_access(&req);
 
-    let category = find_category(&state.db, user_id, *id).await?;
-    Ok(HttpResponse::Ok().json(category))
+    let category = find_category(&state.db, &access, *id).await?;
+    Ok(HttpResponse::Ok().json(CategoryResponse::from(category)))
 }
 
 #[put("/{id}")]
@@ -87,19 +153,19 @@ pub async fn update_category(
     id: web::Path<i32>,
     data: web::Json<UpdateCategoryRequest>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
-    let category = find_category(&state.db, user_id, *id).await?;
+    let access = get_workspace_access(&req);
+    let category = find_category(&state.db, &access, *id).await?;
 
     let mut updated_category: CategoryActiveModel = category.into();
     updated_category.name = Set(data.name.clone());
     updated_category.description = Set(data.description.clone());
-    updated_category.updated_at = Set(chrono::Utc::now().into());
+    updated_category.updated_at = Set(chrono::Utc::now());
 
     let updated = updated_category.update(&state.db).await.map_err(|e| {
         actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
     })?;
 
-    Ok(HttpResponse::Ok().json(updated))
+    Ok(HttpResponse::Ok().json(CategoryResponse::from(updated)))
 }
 
 #[delete("/{id}")]
@@ -108,16 +174,65 @@ pub async fn delete_category(
     req: HttpRequest,
     id: web::Path<i32>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
-    let category = find_category(&state.db, user_id, *id).await?;
+    let access = get_workspace_access(&req);
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    let category = find_category(&txn, &access, *id).await?;
+    // The workspace lock comes before the transaction rows, as in every
+    // ledger write
+    lock_workspace(&txn, &access).await?;
+
+    // The category's transactions go through the ledger first, so their
+    // account postings do not outlive it. Transfers touching the category are
+    // removed with both legs.
+    let transactions = Transaction::find()
+        .filter(
+            transaction::Column::CategoryId
+                .eq(category.id)
+                .or(transaction::Column::TransferId.in_subquery(
+                    Query::select()
+                        .column(transaction::Column::TransferId)
+                        .from(Transaction)
+                        .and_where(transaction::Column::CategoryId.eq(category.id))
+                        .and_where(transaction::Column::TransferId.is_not_null())
+                        .to_owned(),
+                )),
+        )
+        .lock_exclusive()
+        .all(&txn)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if !transactions.is_empty() {
+        delete_legs(&txn, &access, &transactions).await?;
+        Transfer::delete_many()
+            .filter(
+                transfer::Column::Id.is_in(
+                    transactions
+                        .iter()
+                        .filter_map(|transaction| transaction.transfer_id),
+                ),
+            )
+            .exec(&txn)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+    }
 
     Category::delete_by_id(category.id)
-        .exec(&state.db)
+        .exec(&txn)
         .await
         .map_err(|e| {
             actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
         })?;
 
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
     Ok(HttpResponse::Ok().json(json!({"status": "success"})))
 }
 
@@ -127,8 +242,13 @@ pub async fn get_category_transactions(
     req: HttpRequest,
     id: web::Path<i32>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
-    let _category = find_category(&state.db, user_id, *id).await?;
+    let access = get_workspace_access(&req);
+    let _category = find_category(&state.db, &access, *id).await?;
+    let base_currency = base_currency(&state.db, access.workspace_id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
 
     let transactions = Transaction::find()
         .filter(crate::entities::transaction::Column::CategoryId.eq(*id))
@@ -138,19 +258,24 @@ pub async fn get_category_transactions(
             actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
         })?;
 
+    let transactions: Vec<TransactionResponse> = transactions
+        .into_iter()
+        .map(|transaction| TransactionResponse::new(transaction, &base_currency))
+        .collect();
     Ok(HttpResponse::Ok().json(transactions))
 }
 
-async fn find_category(
-    db: &DatabaseConnection,
-    user_id: i32,
+async fn find_category<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
     category_id: i32,
 ) -> Result<CategoryModel, actix_web::Error> {
     Category::find_by_id(category_id)
-        .filter(crate::entities::category::Column::UserId.eq(user_id))
+        .filter(crate::entities::category::Column::WorkspaceId.eq(access.workspace_id))
         .one(db)
         .await
         .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .filter(|category| access.allows_category(category.id))
         .ok_or_else(|| {
             actix_web::error::ErrorNotFound(
                 json!({"status": "error", "message": "Not found or unauthorized"}),
@@ -0,0 +1,138 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    AppState,
+    entities::exchange_rate::{self, Entity as ExchangeRate},
+    entities::sea_orm_active_enums::RateSource,
+    utils::auth::get_workspace_access,
+    utils::exchange_rates::{RateRow, check_rate, parse_rates, save_rates, validate_currency},
+    utils::validation::invalid_request,
+};
+
+#[derive(Deserialize, Debug)]
+pub struct ExchangeRateRequest {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub rate: Decimal,
+    pub effective_on: Option<NaiveDate>,
+}
+
+#[get("")]
+pub async fn get_all_exchange_rates(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = get_workspace_access(&req);
+
+    let rates = ExchangeRate::find()
+        .filter(exchange_rate::Column::WorkspaceId.eq(access.workspace_id))
+        .order_by_asc(exchange_rate::Column::FromCurrency)
+        .order_by_asc(exchange_rate::Column::ToCurrency)
+        .order_by_desc(exchange_rate::Column::EffectiveOn)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(rates))
+}
+
+/// Enters a rate by hand. A rate already entered for the same pair and day is
+/// replaced.
+#[post("")]
+pub async fn create_exchange_rate(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<ExchangeRateRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = get_workspace_access(&req);
+    access.require_whole_workspace("Exchange rates need access to the whole workspace")?;
+
+    let from_currency = validate_currency(&data.from_currency)?;
+    let to_currency = validate_currency(&data.to_currency)?;
+    if from_currency == to_currency {
+        return Err(invalid_request("Both currencies must differ"));
+    }
+    let rate = check_rate(data.rate).ok_or_else(|| invalid_request("rate must be positive"))?;
+    let effective_on = data.effective_on.unwrap_or_else(|| Utc::now().date_naive());
+
+    let row = RateRow {
+        from_currency,
+        to_currency,
+        rate,
+        effective_on,
+    };
+    save_rates(
+        &state.db,
+        access.workspace_id,
+        std::slice::from_ref(&row),
+        RateSource::Manual,
+    )
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    let saved = ExchangeRate::find()
+        .filter(exchange_rate::Column::WorkspaceId.eq(access.workspace_id))
+        .filter(exchange_rate::Column::FromCurrency.eq(row.from_currency))
+        .filter(exchange_rate::Column::ToCurrency.eq(row.to_currency))
+        .filter(exchange_rate::Column::EffectiveOn.eq(row.effective_on))
+        .one(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Exchange rate missing"))?;
+
+    Ok(HttpResponse::Created().json(saved))
+}
+
+/// Imports a CSV file of `effective_on,from_currency,to_currency,rate` lines
+/// sent as the request body. Nothing is stored when any line is invalid.
+#[post("/import")]
+pub async fn import_exchange_rates(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: String,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = get_workspace_access(&req);
+    access.require_whole_workspace("Exchange rates need access to the whole workspace")?;
+
+    let rows = parse_rates(&body).map_err(|message| invalid_request(&message))?;
+    save_rates(&state.db, access.workspace_id, &rows, RateSource::Import)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success", "imported": rows.len()})))
+}
+
+#[delete("/{id}")]
+pub async fn delete_exchange_rate(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = get_workspace_access(&req);
+    access.require_whole_workspace("Exchange rates need access to the whole workspace")?;
+
+    let result = ExchangeRate::delete_many()
+        .filter(exchange_rate::Column::Id.eq(*id))
+        .filter(exchange_rate::Column::WorkspaceId.eq(access.workspace_id))
+        .exec(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if result.rows_affected == 0 {
+        return Err(actix_web::error::ErrorNotFound(
+            json!({"status": "error", "message": "Exchange rate not found or unauthorized"}),
+        ));
+    }
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
@@ -0,0 +1,367 @@
+use std::collections::{BTreeSet, HashMap};
+
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    AppState,
+    controllers::workspaces::{find_member, find_workspace, require_owner},
+    entities::access_grant::{
+        self, ActiveModel as AccessGrantActiveModel, Entity as AccessGrant,
+        Model as AccessGrantModel,
+    },
+    entities::access_grant_category::{
+        ActiveModel as AccessGrantCategoryActiveModel, Entity as AccessGrantCategory,
+    },
+    entities::category::{self, Entity as Category},
+    entities::sea_orm_active_enums::GrantAccess,
+    entities::user::{self, Entity as User},
+    entities::workspace::Entity as Workspace,
+    mailer::Email,
+    utils::access_grants::grant_category_ids,
+    utils::auth::get_user_by_id,
+    utils::validation::invalid_request,
+    utils::workspaces::find_membership,
+};
+
+const MAX_GRANT_DAYS: i64 = 365;
+
+#[derive(Deserialize, Debug)]
+pub struct GrantRequest {
+    pub email: String,
+    pub access: GrantAccess,
+    /// Categories the grantee may see. Omit to share the whole workspace.
+    pub category_ids: Option<Vec<i32>>,
+    pub expires_in_days: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GrantResponse {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub workspace_name: String,
+    pub grantee_id: i32,
+    pub grantee_email: String,
+    pub access: GrantAccess,
+    pub category_ids: Option<Vec<i32>>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl GrantResponse {
+    fn new(
+        grant: AccessGrantModel,
+        workspace_name: String,
+        grantee_email: String,
+        category_ids: Option<Vec<i32>>,
+    ) -> Self {
+        Self {
+            id: grant.id,
+            workspace_id: grant.workspace_id,
+            workspace_name,
+            grantee_id: grant.grantee_id,
+            grantee_email,
+            access: grant.access,
+            category_ids,
+            expires_at: grant.expires_at,
+            revoked_at: grant.revoked_at,
+            created_at: grant.created_at,
+        }
+    }
+}
+
+#[get("/{id}/grants")]
+pub async fn get_workspace_grants(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let member = find_member(&state.db, get_user_by_id(&req), *id).await?;
+    require_owner(&member)?;
+    let workspace = find_workspace(&state.db, member.workspace_id).await?;
+
+    let grants = AccessGrant::find()
+        .filter(access_grant::Column::WorkspaceId.eq(workspace.id))
+        .order_by_desc(access_grant::Column::CreatedAt)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    let emails: HashMap<i32, String> = User::find()
+        .filter(user::Column::Id.is_in(grants.iter().map(|grant| grant.grantee_id)))
+        .all(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .into_iter()
+        .map(|user| (user.id, user.email))
+        .collect();
+
+    let mut response = Vec::with_capacity(grants.len());
+    for grant in grants {
+        let category_ids = category_ids_of(&state, &grant).await?;
+        let grantee_email = emails.get(&grant.grantee_id).cloned().unwrap_or_default();
+        response.push(GrantResponse::new(
+            grant,
+            workspace.name.clone(),
+            grantee_email,
+            category_ids,
+        ));
+    }
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Gives an existing Spenser user time-limited access to the workspace
+/// without making them a member.
+#[post("/{id}/grants")]
+pub async fn create_grant(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    data: web::Json<GrantRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+    let member = find_member(&state.db, user_id, *id).await?;
+    require_owner(&member)?;
+    let workspace = find_workspace(&state.db, member.workspace_id).await?;
+
+    if !(1..=MAX_GRANT_DAYS).contains(&data.expires_in_days) {
+        return Err(invalid_request(&format!(
+            "expires_in_days must be between 1 and {}",
+            MAX_GRANT_DAYS
+        )));
+    }
+
+    let grantee = User::find()
+        .filter(user::Column::Email.eq(data.email.trim()))
+        .one(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(json!({
+                "status": "error",
+                "message": "No Spenser account uses this email address"
+            }))
+        })?;
+    if find_membership(&state.db, grantee.id, Some(workspace.id))
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .is_some()
+    {
+        return Err(actix_web::error::ErrorConflict(json!({
+            "status": "error",
+            "message": "This person is already a member of the workspace"
+        })));
+    }
+
+    let category_ids = match &data.category_ids {
+        Some(ids) => {
+            let ids: BTreeSet<i32> = ids.iter().copied().collect();
+            if ids.is_empty() {
+                return Err(invalid_request(
+                    "category_ids must not be empty. Omit it to share every category.",
+                ));
+            }
+            let found = Category::find()
+                .filter(category::Column::WorkspaceId.eq(workspace.id))
+                .filter(category::Column::Id.is_in(ids.iter().copied()))
+                .count(&state.db)
+                .await
+                .map_err(|e| {
+                    actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+                })?;
+            if found != ids.len() as u64 {
+                return Err(invalid_request(
+                    "Every category must belong to this workspace",
+                ));
+            }
+            Some(ids.into_iter().collect::<Vec<i32>>())
+        }
+        None => None,
+    };
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    let grant = AccessGrantActiveModel {
+        workspace_id: Set(workspace.id),
+        grantor_id: Set(Some(user_id)),
+        grantee_id: Set(grantee.id),
+        access: Set(data.access),
+        all_categories: Set(category_ids.is_none()),
+        expires_at: Set(Utc::now() + Duration::days(data.expires_in_days)),
+        revoked_at: Set(None),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+    if let Some(ids) = &category_ids {
+        AccessGrantCategory::insert_many(ids.iter().map(|&category_id| {
+            AccessGrantCategoryActiveModel {
+                access_grant_id: Set(grant.id),
+                category_id: Set(category_id),
+            }
+        }))
+        .exec(&txn)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    }
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let email = Email {
+        to: grantee.email.clone(),
+        subject: format!(
+            "You have been given access to \"{}\" on Spenser",
+            workspace.name
+        ),
+        body: format!(
+            "You now have {} access to the \"{}\" budget on Spenser until {}.\n\n\
+             Send the header X-Access-Grant: {} with your requests to use it.",
+            match grant.access {
+                GrantAccess::Read => "read-only",
+                GrantAccess::Write => "read and write",
+            },
+            workspace.name,
+            grant.expires_at.format("%Y-%m-%d"),
+            grant.id
+        ),
+    };
+    if let Err(e) = state.mailer.send(&email).await {
+        eprintln!("❌ Failed to send access grant notice: {}", e);
+    }
+
+    Ok(HttpResponse::Created().json(GrantResponse::new(
+        grant,
+        workspace.name,
+        grantee.email,
+        category_ids,
+    )))
+}
+
+#[delete("/{id}/grants/{grant_id}")]
+pub async fn revoke_grant(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (workspace_id, grant_id) = path.into_inner();
+    let member = find_member(&state.db, get_user_by_id(&req), workspace_id).await?;
+    require_owner(&member)?;
+
+    revoke(
+        &state,
+        access_grant::Column::WorkspaceId.eq(workspace_id),
+        grant_id,
+    )
+    .await
+}
+
+/// Active grants the signed-in user has received.
+#[get("")]
+pub async fn get_received_grants(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+    let grantee = User::find_by_id(user_id)
+        .one(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
+
+    let grants = AccessGrant::find()
+        .filter(access_grant::Column::GranteeId.eq(user_id))
+        .filter(access_grant::Column::RevokedAt.is_null())
+        .filter(access_grant::Column::ExpiresAt.gt(Utc::now()))
+        .order_by_asc(access_grant::Column::Id)
+        .find_also_related(Workspace)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let mut response = Vec::with_capacity(grants.len());
+    for (grant, workspace) in grants {
+        let category_ids = category_ids_of(&state, &grant).await?;
+        response.push(GrantResponse::new(
+            grant,
+            workspace
+                .map(|workspace| workspace.name)
+                .unwrap_or_default(),
+            grantee.email.clone(),
+            category_ids,
+        ));
+    }
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Lets a grantee give up access they no longer need.
+#[delete("/{grant_id}")]
+pub async fn decline_grant(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    grant_id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    revoke(
+        &state,
+        access_grant::Column::GranteeId.eq(get_user_by_id(&req)),
+        *grant_id,
+    )
+    .await
+}
+
+// Helpers
+
+async fn revoke(
+    state: &AppState,
+    scope: sea_orm::sea_query::SimpleExpr,
+    grant_id: i32,
+) -> Result<HttpResponse, actix_web::Error> {
+    let revoked = AccessGrant::update_many()
+        .col_expr(
+            access_grant::Column::RevokedAt,
+            sea_orm::sea_query::Expr::value(Utc::now()),
+        )
+        .filter(access_grant::Column::Id.eq(grant_id))
+        .filter(scope)
+        .filter(access_grant::Column::RevokedAt.is_null())
+        .exec(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if revoked.rows_affected == 0 {
+        return Err(actix_web::error::ErrorNotFound(
+            json!({"status": "error", "message": "Access grant not found"}),
+        ));
+    }
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+async fn category_ids_of(
+    state: &AppState,
+    grant: &AccessGrantModel,
+) -> Result<Option<Vec<i32>>, actix_web::Error> {
+    if grant.all_categories {
+        return Ok(None);
+    }
+    grant_category_ids(&state.db, grant.id)
+        .await
+        .map(Some)
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))
+}
@@ -0,0 +1,283 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, put, web};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    AppState,
+    controllers::auth::send_verification_email,
+    controllers::workspaces::{WorkspaceResponse, member_workspaces},
+    entities::login_attempt::{self, Entity as LoginAttempt},
+    entities::sea_orm_active_enums::WorkspaceRole,
+    entities::user::{self, ActiveModel as UserActiveModel, Entity as User, Model as UserModel},
+    entities::workspace::Entity as Workspace,
+    entities::workspace_member::{self, Entity as WorkspaceMember},
+    utils::auth::{get_claims, get_user_by_id},
+    utils::password::{hash_password, verify_password},
+    utils::personal_access_tokens::revoke_personal_access_tokens,
+    utils::responses::unauthorized,
+    utils::sessions::revoke_other_sessions,
+    utils::validation::validate_email,
+    utils::workspaces::count_owners,
+};
+
+pub const LOGIN_HISTORY_LIMIT: u64 = 50;
+
+#[derive(Deserialize, Debug)]
+pub struct UpdateProfileRequest {
+    pub firstname: Option<String>,
+    pub lastname: Option<String>,
+    pub email: Option<String>,
+    /// Required when `email` changes.
+    pub current_password: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+}
+
+/// The user with the balance of every workspace they belong to, since
+/// balances are kept per workspace.
+#[derive(Serialize, Debug)]
+pub struct ProfileResponse {
+    #[serde(flatten)]
+    pub user: UserModel,
+    pub workspaces: Vec<WorkspaceResponse>,
+}
+
+#[get("")]
+pub async fn get_profile(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = find_user(&state.db, get_user_by_id(&req)).await?;
+    let workspaces = member_workspaces(&state.db, user.id).await?;
+    Ok(HttpResponse::Ok().json(ProfileResponse { user, workspaces }))
+}
+
+#[put("")]
+pub async fn update_profile(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<UpdateProfileRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = find_user(&state.db, get_user_by_id(&req)).await?;
+
+    let email_changed = data
+        .email
+        .as_ref()
+        .is_some_and(|email| *email != user.email);
+    if email_changed {
+        // The email receives password resets and magic links, so moving it
+        // elsewhere needs the same proof as changing the password.
+        let current_password = data.current_password.as_deref().unwrap_or_default();
+        if !verify_password(current_password, &user.password)? {
+            return Err(unauthorized("Current password is incorrect"));
+        }
+        let email = data.email.as_deref().unwrap_or_default();
+        validate_email(email)?;
+        let taken = User::find()
+            .filter(user::Column::Email.eq(email))
+            .filter(user::Column::Id.ne(user.id))
+            .one(&state.db)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?
+            .is_some();
+        if taken {
+            return Err(actix_web::error::ErrorUnprocessableEntity(json!({
+                "status": "error",
+                "message": "Email already exists."
+            })));
+        }
+    }
+
+    let mut updated_user: UserActiveModel = user.into();
+    if let Some(first_name) = &data.firstname {
+        updated_user.first_name = Set(first_name.clone());
+    }
+    if let Some(last_name) = &data.lastname {
+        updated_user.last_name = Set(last_name.clone());
+    }
+    if email_changed {
+        updated_user.email = Set(data.email.clone().unwrap_or_default());
+        updated_user.verified_at = Set(None);
+    }
+    updated_user.updated_at = Set(Utc::now());
+
+    let updated = updated_user.update(&state.db).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    if email_changed {
+        send_verification_email(&state, &updated).await?;
+    }
+
+    let workspaces = member_workspaces(&state.db, updated.id).await?;
+    Ok(HttpResponse::Ok().json(ProfileResponse {
+        user: updated,
+        workspaces,
+    }))
+}
+
+#[put("/password")]
+pub async fn change_password(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<ChangePasswordRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let claims = get_claims(&req)?;
+    let user = find_user(&state.db, claims.sub).await?;
+
+    if !verify_password(&data.current_password, &user.password)? {
+        return Err(unauthorized("Current password is incorrect"));
+    }
+    state.password_policy.validate(&data.new_password)?;
+    let hashed_password = hash_password(&state.password_policy, &data.new_password)?;
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    UserActiveModel {
+        id: Set(user.id),
+        password: Set(hashed_password),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .update(&txn)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    // Keep the caller signed in; every other device has to sign in again
+    // and personal access tokens have to be created anew.
+    revoke_other_sessions(&txn, user.id, &claims.sid)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    revoke_personal_access_tokens(&txn, user.id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "Password updated. Other sessions have been signed out and access tokens revoked."
+    })))
+}
+
+#[delete("")]
+pub async fn delete_account(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<DeleteAccountRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = find_user(&state.db, get_user_by_id(&req)).await?;
+
+    if !verify_password(&data.password, &user.password)? {
+        return Err(unauthorized("Password is incorrect"));
+    }
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    // Workspaces nobody else uses are deleted with the account. Shared ones
+    // keep their data, but must not be left without an owner.
+    let memberships = WorkspaceMember::find()
+        .filter(workspace_member::Column::UserId.eq(user.id))
+        .all(&txn)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    for membership in memberships {
+        let members = WorkspaceMember::find()
+            .filter(workspace_member::Column::WorkspaceId.eq(membership.workspace_id))
+            .count(&txn)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+        if members == 1 {
+            Workspace::delete_by_id(membership.workspace_id)
+                .exec(&txn)
+                .await
+                .map_err(|e| {
+                    actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+                })?;
+            continue;
+        }
+
+        let owners = count_owners(&txn, membership.workspace_id)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+        if membership.role == WorkspaceRole::Owner && owners == 1 {
+            return Err(actix_web::error::ErrorConflict(json!({
+                "status": "error",
+                "message": "You are the only owner of a shared workspace. Make another member an owner first."
+            })));
+        }
+    }
+
+    // Sessions, tokens, memberships and sign-in history are removed by the
+    // `ON DELETE CASCADE` foreign keys; shared categories and transactions
+    // keep their data with the creator cleared.
+    User::delete_by_id(user.id).exec(&txn).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+#[get("/login-attempts")]
+pub async fn get_login_attempts(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+
+    let attempts = LoginAttempt::find()
+        .filter(login_attempt::Column::UserId.eq(user_id))
+        .order_by_desc(login_attempt::Column::CreatedAt)
+        .limit(LOGIN_HISTORY_LIMIT)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(attempts))
+}
+
+async fn find_user(db: &DatabaseConnection, id: i32) -> Result<UserModel, actix_web::Error> {
+    User::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| unauthorized("User not found"))
+}
@@ -1,3 +1,16 @@
+pub mod accounts;
+pub mod admin;
 pub mod auth;
+pub mod balances;
 pub mod categories;
+pub mod exchange_rates;
+pub mod grants;
+pub mod me;
+pub mod oidc;
+pub mod sessions;
+pub mod tokens;
 pub mod transactions;
+pub mod transfers;
+pub mod two_factor;
+pub mod well_known;
+pub mod workspaces;
@@ -0,0 +1,271 @@
+use actix_web::{HttpRequest, HttpResponse, get, web};
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    AppState,
+    controllers::auth::complete_sign_in,
+    entities::oidc_login_request::{
+        self, ActiveModel as OidcLoginRequestActiveModel, Entity as OidcLoginRequest,
+    },
+    entities::user::{self, ActiveModel as UserActiveModel, Entity as User, Model as UserModel},
+    entities::user_identity::{
+        self, ActiveModel as UserIdentityActiveModel, Entity as UserIdentity,
+    },
+    utils::auth::client_info,
+    utils::cookies::{OIDC_STATE_COOKIE, oidc_state_cookie},
+    utils::login_attempts::check_not_locked,
+    utils::oidc::{IdTokenClaims, OidcConfig},
+    utils::password::hash_password,
+    utils::responses::{forbidden, unauthorized},
+    utils::tokens::{generate_token, hash_token},
+    utils::workspaces::{DEFAULT_BASE_CURRENCY, PERSONAL_WORKSPACE_NAME, create_owned_workspace},
+};
+
+/// How long the user has to finish signing in at the provider.
+const LOGIN_REQUEST_LIFETIME: Duration = Duration::minutes(10);
+
+#[derive(Deserialize, Debug)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+#[get("/oidc/start")]
+pub async fn oidc_start(state: web::Data<AppState>) -> Result<HttpResponse, actix_web::Error> {
+    let config = oidc_config(&state)?;
+    let metadata = config.discover().await?;
+
+    let login_state = generate_token();
+    let nonce = generate_token();
+    let code_verifier = generate_token();
+
+    // Abandoned requests are cleaned up whenever a new one starts
+    OidcLoginRequest::delete_many()
+        .filter(oidc_login_request::Column::ExpiresAt.lt(Utc::now()))
+        .exec(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    OidcLoginRequestActiveModel {
+        state_hash: Set(hash_token(&login_state)),
+        nonce: Set(nonce.clone()),
+        code_verifier: Set(code_verifier.clone()),
+        expires_at: Set(Utc::now() + LOGIN_REQUEST_LIFETIME),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    let location = config.authorization_url(&metadata, &login_state, &nonce, &code_verifier)?;
+    Ok(HttpResponse::Found()
+        .insert_header(("Location", location))
+        .cookie(oidc_state_cookie(login_state, LOGIN_REQUEST_LIFETIME))
+        .finish())
+}
+
+#[get("/oidc/callback")]
+pub async fn oidc_callback(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<CallbackQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let config = oidc_config(&state)?;
+
+    if let Some(error) = &query.error {
+        return Err(unauthorized(&format!(
+            "Sign-in was not completed: {}",
+            query.error_description.as_deref().unwrap_or(error)
+        )));
+    }
+    let (Some(code), Some(login_state)) = (&query.code, &query.state) else {
+        return Err(actix_web::error::ErrorBadRequest(json!({
+            "status": "error",
+            "message": "Missing code or state"
+        })));
+    };
+
+    // The state must come back to the browser that started the sign-in, so
+    // a code obtained elsewhere cannot sign this browser into another account
+    let started_here = req
+        .cookie(OIDC_STATE_COOKIE)
+        .is_some_and(|cookie| hash_token(cookie.value()) == hash_token(login_state));
+    if !started_here {
+        return Err(unauthorized("Sign-in was started in another browser"));
+    }
+
+    let request = OidcLoginRequest::find()
+        .filter(oidc_login_request::Column::StateHash.eq(hash_token(login_state)))
+        .one(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| unauthorized("Unknown or expired sign-in request"))?;
+
+    // Deleting the request makes it single-use, even for concurrent callbacks
+    let deleted = OidcLoginRequest::delete_by_id(request.id)
+        .exec(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if deleted.rows_affected == 0 || request.expires_at < Utc::now() {
+        return Err(unauthorized("Unknown or expired sign-in request"));
+    }
+
+    let metadata = config.discover().await?;
+    let claims = config
+        .exchange_code(&metadata, code, &request.code_verifier, &request.nonce)
+        .await?;
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    let user = find_or_provision_user(&txn, &state, config, &claims).await?;
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    if user.disabled_at.is_some() {
+        return Err(forbidden("Account is disabled"));
+    }
+    check_not_locked(&user)?;
+
+    // The callback is a browser navigation, so it cannot ask for cookie mode
+    // with a header; use cookies whenever they are enabled
+    let cookie_mode = state.cookies.is_some();
+    let mut response = complete_sign_in(&state, &user, &client_info(&req), cookie_mode).await?;
+    let mut removal = oidc_state_cookie(String::new(), Duration::zero());
+    removal.make_removal();
+    response.add_cookie(&removal)?;
+    Ok(response)
+}
+
+/// Resolves the Spenser user behind an external identity. Unknown identities
+/// are linked to the account with the same email when the provider has
+/// verified that address, or get a new account when auto-provisioning is on.
+async fn find_or_provision_user<C: ConnectionTrait>(
+    db: &C,
+    state: &AppState,
+    config: &OidcConfig,
+    claims: &IdTokenClaims,
+) -> Result<UserModel, actix_web::Error> {
+    let identity = UserIdentity::find()
+        .filter(user_identity::Column::Issuer.eq(&config.issuer))
+        .filter(user_identity::Column::Subject.eq(&claims.sub))
+        .find_also_related(User)
+        .one(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    if let Some((identity, Some(user))) = identity {
+        let mut seen: UserIdentityActiveModel = identity.into();
+        seen.email = Set(claims.email.clone());
+        seen.last_login_at = Set(Some(Utc::now()));
+        seen.update(db).await.map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+        return Ok(user);
+    }
+
+    let Some(email) = &claims.email else {
+        return Err(forbidden(
+            "The identity provider did not share an email address",
+        ));
+    };
+
+    let existing = User::find()
+        .filter(user::Column::Email.eq(email))
+        .one(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let user = match existing {
+        Some(user) if claims.email_verified => user,
+        Some(_) => {
+            return Err(actix_web::error::ErrorConflict(json!({
+                "status": "error",
+                "message": "An account with this email already exists. Sign in with your password instead."
+            })));
+        }
+        None if config.auto_provision => {
+            let (first_name, last_name) = names(claims);
+            let user = UserActiveModel {
+                email: Set(email.clone()),
+                // Nobody knows this password; a reset link can set a real one
+                password: Set(hash_password(&state.password_policy, &generate_token())?),
+                first_name: Set(first_name),
+                last_name: Set(last_name),
+                created_at: Set(Utc::now()),
+                updated_at: Set(Utc::now()),
+                verified_at: Set(claims.email_verified.then(Utc::now)),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+            create_owned_workspace(db, user.id, PERSONAL_WORKSPACE_NAME, DEFAULT_BASE_CURRENCY)
+                .await
+                .map_err(|e| {
+                    actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+                })?;
+            user
+        }
+        None => return Err(forbidden("No Spenser account is linked to this identity")),
+    };
+
+    UserIdentityActiveModel {
+        user_id: Set(user.id),
+        issuer: Set(config.issuer.clone()),
+        subject: Set(claims.sub.clone()),
+        email: Set(claims.email.clone()),
+        last_login_at: Set(Some(Utc::now())),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(user)
+}
+
+/// First and last name from the standard claims, falling back to splitting
+/// `name` at its first space.
+fn names(claims: &IdTokenClaims) -> (String, String) {
+    if claims.given_name.is_some() || claims.family_name.is_some() {
+        return (
+            claims.given_name.clone().unwrap_or_default(),
+            claims.family_name.clone().unwrap_or_default(),
+        );
+    }
+    match claims.name.as_deref().map(|name| name.split_once(' ')) {
+        Some(Some((first, last))) => (first.to_string(), last.to_string()),
+        Some(None) => (claims.name.clone().unwrap_or_default(), String::new()),
+        None => (String::new(), String::new()),
+    }
+}
+
+fn oidc_config(state: &AppState) -> Result<&OidcConfig, actix_web::Error> {
+    state.oidc.as_ref().ok_or_else(|| {
+        actix_web::error::ErrorNotFound(json!({
+            "status": "error",
+            "message": "OpenID Connect sign-in is not configured"
+        }))
+    })
+}
@@ -0,0 +1,104 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, web};
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    AppState,
+    entities::session::{self, Entity as Session, Model as SessionModel},
+    utils::auth::get_claims,
+    utils::sessions::{revoke_session, revoke_user_sessions},
+};
+
+#[derive(Serialize, Debug)]
+pub struct SessionResponse {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// Whether this is the session making the request.
+    pub current: bool,
+}
+
+impl SessionResponse {
+    fn new(session: SessionModel, current_session_id: &str) -> Self {
+        Self {
+            current: session.id == current_session_id,
+            id: session.id,
+            user_agent: session.user_agent,
+            ip: session.ip,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+        }
+    }
+}
+
+#[get("")]
+pub async fn get_all_sessions(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let claims = get_claims(&req)?;
+
+    let sessions = Session::find()
+        .filter(session::Column::UserId.eq(claims.sub))
+        .filter(session::Column::RevokedAt.is_null())
+        .order_by_desc(session::Column::LastSeenAt)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let sessions: Vec<SessionResponse> = sessions
+        .into_iter()
+        .map(|session| SessionResponse::new(session, &claims.sid))
+        .collect();
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+#[delete("/{id}")]
+pub async fn revoke_one_session(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let claims = get_claims(&req)?;
+
+    let session = Session::find_by_id(id.into_inner())
+        .filter(session::Column::UserId.eq(claims.sub))
+        .filter(session::Column::RevokedAt.is_null())
+        .one(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(
+                json!({"status": "error", "message": "Session not found"}),
+            )
+        })?;
+
+    revoke_session(&state.db, &session.id).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+/// Signs out everywhere, including the session making the request.
+#[delete("")]
+pub async fn revoke_all_sessions(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let claims = get_claims(&req)?;
+
+    revoke_user_sessions(&state.db, claims.sub)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
@@ -0,0 +1,151 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    AppState,
+    entities::personal_access_token::{
+        self, ActiveModel as PersonalAccessTokenActiveModel, Entity as PersonalAccessToken,
+        Model as PersonalAccessTokenModel,
+    },
+    utils::auth::get_user_by_id,
+    utils::personal_access_tokens::{SCOPES, create_personal_access_token},
+    utils::validation::invalid_request,
+};
+
+#[derive(Deserialize, Debug)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TokenResponse {
+    pub id: i32,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<PersonalAccessTokenModel> for TokenResponse {
+    fn from(token: PersonalAccessTokenModel) -> Self {
+        Self {
+            id: token.id,
+            name: token.name,
+            token_prefix: token.token_prefix,
+            scopes: token.scopes.split_whitespace().map(String::from).collect(),
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            created_at: token.created_at,
+        }
+    }
+}
+
+#[get("")]
+pub async fn get_all_tokens(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+
+    let tokens = PersonalAccessToken::find()
+        .filter(personal_access_token::Column::UserId.eq(user_id))
+        .filter(personal_access_token::Column::RevokedAt.is_null())
+        .order_by_desc(personal_access_token::Column::CreatedAt)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let tokens: Vec<TokenResponse> = tokens.into_iter().map(TokenResponse::from).collect();
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+#[post("")]
+pub async fn create_token(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<CreateTokenRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+
+    let name = data.name.trim();
+    if name.is_empty() || name.len() > 255 {
+        return Err(invalid_request("Name must be between 1 and 255 characters"));
+    }
+    if data.scopes.is_empty() {
+        return Err(invalid_request("At least one scope is required"));
+    }
+    if let Some(scope) = data
+        .scopes
+        .iter()
+        .find(|scope| !SCOPES.contains(&scope.as_str()))
+    {
+        return Err(invalid_request(&format!("Unknown scope '{}'", scope)));
+    }
+    let expires_at = match data.expires_in_days {
+        Some(days) if !(1..=3650).contains(&days) => {
+            return Err(invalid_request(
+                "expires_in_days must be between 1 and 3650",
+            ));
+        }
+        Some(days) => Some(Utc::now() + Duration::days(days)),
+        None => None,
+    };
+
+    let mut scopes = data.scopes.clone();
+    scopes.sort();
+    scopes.dedup();
+
+    let (token, record) =
+        create_personal_access_token(&state.db, user_id, name, &scopes, expires_at)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+
+    Ok(HttpResponse::Created().json(json!({
+        "token": token,
+        "details": TokenResponse::from(record)
+    })))
+}
+
+#[delete("/{id}")]
+pub async fn revoke_token(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+
+    let token = PersonalAccessToken::find()
+        .filter(personal_access_token::Column::Id.eq(*id))
+        .filter(personal_access_token::Column::UserId.eq(user_id))
+        .filter(personal_access_token::Column::RevokedAt.is_null())
+        .one(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(
+                json!({"status": "error", "message": "Token not found"}),
+            )
+        })?;
+
+    PersonalAccessTokenActiveModel {
+        id: Set(token.id),
+        revoked_at: Set(Some(Utc::now())),
+        ..Default::default()
+    }
+    .update(&state.db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
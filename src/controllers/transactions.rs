@@ -1,35 +1,97 @@
 use actix_web::{HttpRequest, HttpResponse, delete, get, post, put, web};
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
-    Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect, Select,
+    Set, TransactionTrait,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::json;
 
 use crate::{
     AppState,
-    entities::category::Entity as Category,
+    controllers::transfers::delete_transfer_legs,
+    entities::sea_orm_active_enums::TransactionKind,
     entities::transaction::{
         ActiveModel as TransactionActiveModel, Entity as Transaction, Model as TransactionModel,
     },
     entities::user::Entity as User,
-    utils::auth::get_user_by_id,
+    utils::auth::get_workspace_access,
+    utils::exchange_rates::{convert, find_rate},
+    utils::ledger::{
+        Leg, delete_legs, leg_delta, lock_balances, lock_workspace, record_entry, replace_entry,
+    },
+    utils::money::Money,
+    utils::workspaces::{WorkspaceAccess, base_currency},
 };
 
 #[derive(Deserialize, Debug)]
 pub struct CreateTransactionRequest {
+    pub account_id: i32,
     pub category_id: i32,
-    pub r#type: String,
-    pub amount: i64,
+    pub r#type: TransactionKind,
+    pub amount: Money,
+    pub date: Option<NaiveDate>,
     pub memo: String,
     pub description: Option<String>,
 }
 
+/// Fields left out keep their current value. `description` is cleared with
+/// an explicit `null`.
 #[derive(Deserialize, Debug)]
 pub struct UpdateTransactionRequest {
+    pub category_id: Option<i32>,
+    pub r#type: Option<TransactionKind>,
+    pub amount: Option<Money>,
+    pub date: Option<NaiveDate>,
+    pub memo: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub description: Option<Option<String>>,
+}
+
+/// A transaction with its amounts in its own and in the workspace's base
+/// currency.
+#[derive(Serialize, Debug)]
+pub struct TransactionResponse {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub user_id: Option<i32>,
+    pub account_id: i32,
+    pub category_id: i32,
+    pub r#type: TransactionKind,
+    pub amount: Money,
+    pub exchange_rate: Decimal,
+    pub base_amount: Money,
+    pub date: NaiveDate,
     pub memo: String,
     pub description: Option<String>,
+    pub transfer_id: Option<i32>,
+    pub journal_entry_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TransactionResponse {
+    pub fn new(transaction: TransactionModel, base_currency: &str) -> Self {
+        Self {
+            id: transaction.id,
+            workspace_id: transaction.workspace_id,
+            user_id: transaction.user_id,
+            account_id: transaction.account_id,
+            category_id: transaction.category_id,
+            r#type: transaction.r#type,
+            amount: Money::new(transaction.amount, transaction.currency),
+            exchange_rate: transaction.exchange_rate,
+            base_amount: Money::new(transaction.base_amount, base_currency),
+            date: transaction.date,
+            memo: transaction.memo,
+            description: transaction.description,
+            transfer_id: transaction.transfer_id,
+            journal_entry_id: transaction.journal_entry_id,
+            created_at: transaction.created_at,
+            updated_at: transaction.updated_at,
+        }
+    }
 }
 
 #[get("")]
@@ -37,16 +99,26 @@ pub async fn get_all_transactions(
     state: web::Data<AppState>,
     req: HttpRequest,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
+    let access = get_workspace_access(&req);
+
+    let mut query = Transaction::find()
+        .filter(crate::entities::transaction::Column::WorkspaceId.eq(access.workspace_id));
+    if let Some(category_ids) = access.category_ids {
+        query = query.filter(crate::entities::transaction::Column::CategoryId.is_in(category_ids));
+    }
+    let transactions = query.all(&state.db).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
 
-    let transactions = Transaction::find()
-        .filter(crate::entities::transaction::Column::UserId.eq(user_id))
-        .all(&state.db)
+    let base_currency = base_currency(&state.db, access.workspace_id)
         .await
         .map_err(|e| {
             actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
         })?;
-
+    let transactions: Vec<TransactionResponse> = transactions
+        .into_iter()
+        .map(|transaction| TransactionResponse::new(transaction, &base_currency))
+        .collect();
     Ok(HttpResponse::Ok().json(transactions))
 }
 
@@ -56,43 +128,96 @@ pub async fn create_transaction(
     req: HttpRequest,
     data: web::Json<CreateTransactionRequest>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
-    let db = &state.db;
+    let access = get_workspace_access(&req);
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let user = User::find_by_id(access.user_id)
+        .one(&txn)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
+    let locked = lock_balances(&txn, &access, &[data.account_id], &[data.category_id]).await?;
+
+    if state.require_verified_email && user.verified_at.is_none() {
+        return Err(actix_web::error::ErrorForbidden(json!({
+            "status": "error",
+            "message": "Verify your email address before creating transactions"
+        })));
+    }
 
-    let (user, category) = fetch_user_and_category(db, user_id, data.category_id).await?;
+    // Transfer legs only come in pairs, through `/api/transfers`
+    if data.r#type == TransactionKind::Transfer {
+        return Err(actix_web::error::ErrorBadRequest(json!({
+            "status": "error",
+            "message": "Use /api/transfers to move money between categories or accounts"
+        })));
+    }
+    data.r#type
+        .check_amount(data.amount.minor_units())
+        .map_err(|message| {
+            actix_web::error::ErrorBadRequest(json!({"status": "error", "message": message}))
+        })?;
 
-    if data.r#type == "DEBIT" && (user.balance < data.amount || category.balance < data.amount) {
-        return Err(actix_web::error::ErrorBadRequest(
-            json!({"status": "error", "message": "Insufficient balance"}),
-        ));
+    // The rate to the base currency is recorded with the transaction, so
+    // later rate changes do not rewrite past reports
+    let date = data.date.unwrap_or_else(|| Utc::now().date_naive());
+    let currency = locked.currency(data.account_id, data.category_id)?;
+    if data.amount.currency() != currency {
+        return Err(actix_web::error::ErrorBadRequest(json!({
+            "status": "error",
+            "message": format!("amount must be in {}, the currency of the account", currency)
+        })));
     }
+    let exchange_rate = find_rate(
+        &txn,
+        access.workspace_id,
+        currency,
+        locked.base_currency(),
+        date,
+    )
+    .await?;
+    let base_amount = convert(&data.amount, exchange_rate, locked.base_currency())?;
+    let leg = Leg {
+        account_id: data.account_id,
+        category_id: data.category_id,
+        delta: leg_delta(data.r#type, data.amount.minor_units())?,
+        base_delta: leg_delta(data.r#type, base_amount.minor_units())?,
+    };
+    locked.ensure_covered(std::slice::from_ref(&leg))?;
+    let entry = record_entry(&txn, &access, std::slice::from_ref(&leg)).await?;
 
     let transaction = TransactionActiveModel {
-        user_id: Set(user_id),
+        workspace_id: Set(access.workspace_id),
+        user_id: Set(Some(access.user_id)),
+        account_id: Set(data.account_id),
         category_id: Set(data.category_id),
-        r#type: Set(data.r#type.clone()),
-        amount: Set(data.amount),
+        r#type: Set(data.r#type),
+        amount: Set(data.amount.minor_units()),
+        currency: Set(data.amount.currency().to_string()),
+        exchange_rate: Set(exchange_rate),
+        base_amount: Set(base_amount.minor_units()),
+        date: Set(date),
         memo: Set(data.memo.clone()),
         description: Set(data.description.clone()),
-        created_at: Set(Utc::now().into()),
-        updated_at: Set(Utc::now().into()),
+        journal_entry_id: Set(entry.id),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
         ..Default::default()
     }
-    .insert(db)
+    .insert(&txn)
     .await
     .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
 
-    update_balances(
-        db,
-        user_id,
-        data.category_id,
-        &data.r#type,
-        data.amount,
-        false,
-    )
-    .await?;
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
 
-    Ok(HttpResponse::Created().json(transaction))
+    Ok(HttpResponse::Created().json(TransactionResponse::new(
+        transaction,
+        base_amount.currency(),
+    )))
 }
 
 #[get("/{id}")]
@@ -101,11 +226,20 @@ pub async fn show_transaction(
     req: HttpRequest,
     id: web::Path<i32>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
-    let transaction = find_transaction(&state.db, user_id, *id).await?;
-    Ok(HttpResponse::Ok().json(transaction))
+    let access = get_workspace_access(&req);
+    let transaction = find_transaction(&state.db, &access, *id).await?;
+    let base_currency = base_currency(&state.db, access.workspace_id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    Ok(HttpResponse::Ok().json(TransactionResponse::new(transaction, &base_currency)))
 }
 
+/// Changes a transaction in place, keeping its id and `created_at`. When the
+/// category, type, amount or date change, the old balance effect is reversed
+/// and the new one applied in one database transaction, and both must leave
+/// every balance covered.
 #[put("/{id}")]
 pub async fn update_transaction(
     state: web::Data<AppState>,
@@ -113,19 +247,110 @@ pub async fn update_transaction(
     id: web::Path<i32>,
     data: web::Json<UpdateTransactionRequest>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
-    let transaction = find_transaction(&state.db, user_id, *id).await?;
+    let access = get_workspace_access(&req);
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    // Locked until the update commits, so concurrent edits cannot both
+    // reverse the same entry
+    let transaction = lock_transaction(&txn, &access, *id).await?;
+
+    let category_id = data.category_id.unwrap_or(transaction.category_id);
+    let kind = data.r#type.unwrap_or(transaction.r#type);
+    let amount = data
+        .amount
+        .clone()
+        .unwrap_or_else(|| Money::new(transaction.amount, transaction.currency.clone()));
+    let date = data.date.unwrap_or(transaction.date);
+    let rebook = category_id != transaction.category_id
+        || kind != transaction.r#type
+        || amount.minor_units() != transaction.amount
+        || amount.currency() != transaction.currency
+        || date != transaction.date;
+
+    let mut updated_transaction: TransactionActiveModel = transaction.clone().into();
+    if rebook {
+        // Transfer legs only change together, through `/api/transfers`
+        if transaction.transfer_id.is_some() || kind == TransactionKind::Transfer {
+            return Err(actix_web::error::ErrorBadRequest(json!({
+                "status": "error",
+                "message": "Transfers can only change their memo and description"
+            })));
+        }
+        kind.check_amount(amount.minor_units()).map_err(|message| {
+            actix_web::error::ErrorBadRequest(json!({"status": "error", "message": message}))
+        })?;
 
-    let mut updated_transaction: TransactionActiveModel = transaction.into();
-    updated_transaction.memo = Set(data.memo.clone());
-    updated_transaction.description = Set(data.description.clone());
-    updated_transaction.updated_at = Set(Utc::now().into());
+        let locked = lock_balances(
+            &txn,
+            &access,
+            &[transaction.account_id],
+            &[transaction.category_id, category_id],
+        )
+        .await?;
+
+        let currency = locked.currency(transaction.account_id, category_id)?;
+        if amount.currency() != currency {
+            return Err(actix_web::error::ErrorBadRequest(json!({
+                "status": "error",
+                "message": format!("amount must be in {}, the currency of the account", currency)
+            })));
+        }
+        // The recorded rate stays unless the transaction moves to another day
+        let exchange_rate = if date == transaction.date {
+            transaction.exchange_rate
+        } else {
+            find_rate(
+                &txn,
+                access.workspace_id,
+                currency,
+                locked.base_currency(),
+                date,
+            )
+            .await?
+        };
+        let base_amount = convert(&amount, exchange_rate, locked.base_currency())?;
+        let legs = [
+            Leg::reversal(&transaction)?,
+            Leg {
+                account_id: transaction.account_id,
+                category_id,
+                delta: leg_delta(kind, amount.minor_units())?,
+                base_delta: leg_delta(kind, base_amount.minor_units())?,
+            },
+        ];
+        locked.ensure_covered(&legs)?;
+        let entry = replace_entry(&txn, &access, &transaction, &legs[1]).await?;
+
+        updated_transaction.category_id = Set(category_id);
+        updated_transaction.r#type = Set(kind);
+        updated_transaction.amount = Set(amount.minor_units());
+        updated_transaction.exchange_rate = Set(exchange_rate);
+        updated_transaction.base_amount = Set(base_amount.minor_units());
+        updated_transaction.date = Set(date);
+        updated_transaction.journal_entry_id = Set(entry.id);
+    }
+    if let Some(memo) = &data.memo {
+        updated_transaction.memo = Set(memo.clone());
+    }
+    if let Some(description) = &data.description {
+        updated_transaction.description = Set(description.clone());
+    }
+    updated_transaction.updated_at = Set(Utc::now());
 
-    let updated = updated_transaction.update(&state.db).await.map_err(|e| {
+    let updated = updated_transaction.update(&txn).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    txn.commit().await.map_err(|e| {
         actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
     })?;
 
-    Ok(HttpResponse::Ok().json(updated))
+    let base_currency = base_currency(&state.db, access.workspace_id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    Ok(HttpResponse::Ok().json(TransactionResponse::new(updated, &base_currency)))
 }
 
 #[delete("/{id}")]
@@ -134,131 +359,72 @@ pub async fn delete_transaction(
     req: HttpRequest,
     id: web::Path<i32>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_by_id(&req);
-    let db = &state.db;
-
-    let transaction = find_transaction(db, user_id, *id).await?;
-    let (user, category) = fetch_user_and_category(db, user_id, transaction.category_id).await?;
-
-    if is_credit(&transaction.r#type)
-        && (transaction.amount > user.balance || transaction.amount > category.balance)
-    {
-        return Err(actix_web::error::ErrorBadRequest(
-            json!({"status": "error", "message": "Insufficient balance"}),
-        ));
+    let access = get_workspace_access(&req);
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    // Deleting either leg of a transfer deletes the whole transfer
+    let transaction = lock_transaction(&txn, &access, *id).await?;
+    match transaction.transfer_id {
+        Some(transfer_id) => delete_transfer_legs(&txn, &access, transfer_id).await?,
+        None => delete_legs(&txn, &access, &[transaction]).await?,
     }
 
-    Transaction::delete_by_id(*id).exec(db).await.map_err(|e| {
+    txn.commit().await.map_err(|e| {
         actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
     })?;
 
-    update_balances(
-        db,
-        user_id,
-        transaction.category_id,
-        &transaction.r#type,
-        transaction.amount,
-        true,
-    )
-    .await?;
-
     Ok(HttpResponse::Ok().json(json!({"status": "success"})))
 }
 
 // Helpers
-async fn fetch_user_and_category(
-    db: &DatabaseConnection,
-    user_id: i32,
-    category_id: i32,
-) -> Result<
-    (
-        crate::entities::user::Model,
-        crate::entities::category::Model,
-    ),
-    actix_web::Error,
-> {
-    let user = User::find_by_id(user_id)
-        .one(db)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
-        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
 
-    let category = Category::find_by_id(category_id)
-        .filter(crate::entities::category::Column::UserId.eq(user_id))
-        .one(db)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
-        .ok_or_else(|| {
-            actix_web::error::ErrorNotFound(
-                json!({"status": "error", "message": "Category not found or unauthorized"}),
-            )
-        })?;
+/// Tells a field sent as `null` (`Some(None)`) apart from one left out
+/// (`None`, through `#[serde(default)]`).
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Option<String>>, D::Error> {
+    Option::<String>::deserialize(deserializer).map(Some)
+}
 
-    Ok((user, category))
+async fn find_transaction<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
+    transaction_id: i32,
+) -> Result<TransactionModel, actix_web::Error> {
+    load_transaction(db, access, Transaction::find_by_id(transaction_id)).await
 }
 
-async fn find_transaction(
-    db: &DatabaseConnection,
-    user_id: i32,
+/// Loads a transaction with `SELECT ... FOR UPDATE`, so concurrent changes to
+/// it wait for the surrounding database transaction to end. The workspace is
+/// locked first, in the order `utils::ledger` sets out.
+async fn lock_transaction<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
     transaction_id: i32,
 ) -> Result<TransactionModel, actix_web::Error> {
-    Transaction::find_by_id(transaction_id)
-        .filter(crate::entities::transaction::Column::UserId.eq(user_id))
+    lock_workspace(db, access).await?;
+    load_transaction(
+        db,
+        access,
+        Transaction::find_by_id(transaction_id).lock_exclusive(),
+    )
+    .await
+}
+
+async fn load_transaction<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
+    query: Select<Transaction>,
+) -> Result<TransactionModel, actix_web::Error> {
+    query
+        .filter(crate::entities::transaction::Column::WorkspaceId.eq(access.workspace_id))
         .one(db)
         .await
         .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .filter(|transaction| access.allows_category(transaction.category_id))
         .ok_or_else(|| {
             actix_web::error::ErrorNotFound(
                 json!({"status": "error", "message": "Transaction not found or unauthorized"}),
             )
         })
 }
-
-async fn update_balances(
-    db: &DatabaseConnection,
-    user_id: i32,
-    category_id: i32,
-    r#type: &str,
-    amount: i64,
-    is_delete: bool,
-) -> Result<(), actix_web::Error> {
-    let (user_delta, category_delta) = match (r#type, is_delete) {
-        ("DEBIT", false) => (-amount, -amount),
-        ("CREDIT", false) => (amount, amount),
-        ("DEBIT", true) => (amount, amount),
-        ("CREDIT", true) => (-amount, -amount),
-        _ => {
-            return Err(actix_web::error::ErrorBadRequest(
-                "Invalid transaction type",
-            ));
-        }
-    };
-
-    let mut user = User::find_by_id(user_id)
-        .one(db)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
-        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))?
-        .into_active_model();
-    user.balance = Set(user.balance.unwrap() + user_delta);
-    user.update(db).await.map_err(|e| {
-        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
-    })?;
-
-    let mut category = Category::find_by_id(category_id)
-        .one(db)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
-        .ok_or_else(|| actix_web::error::ErrorNotFound("Category not found"))?
-        .into_active_model();
-    category.balance = Set(category.balance.unwrap() + category_delta);
-    category.update(db).await.map_err(|e| {
-        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
-    })?;
-
-    Ok(())
-}
-
-fn is_credit(r#type: &str) -> bool {
-    r#type == "CREDIT"
-}
@@ -0,0 +1,326 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    AppState,
+    controllers::transactions::TransactionResponse,
+    entities::sea_orm_active_enums::TransactionKind,
+    entities::transaction::{
+        self, ActiveModel as TransactionActiveModel, Entity as Transaction,
+        Model as TransactionModel,
+    },
+    entities::transfer::{
+        self, ActiveModel as TransferActiveModel, Entity as Transfer, Model as TransferModel,
+    },
+    entities::user::Entity as User,
+    utils::auth::get_workspace_access,
+    utils::exchange_rates::{convert, find_rate},
+    utils::ledger::{Leg, delete_legs, lock_balances, lock_workspace, record_entry},
+    utils::money::{Money, positive},
+    utils::workspaces::{WorkspaceAccess, base_currency},
+};
+
+#[derive(Deserialize, Debug)]
+pub struct CreateTransferRequest {
+    pub from_account_id: i32,
+    pub from_category_id: i32,
+    pub to_account_id: i32,
+    pub to_category_id: i32,
+    #[serde(deserialize_with = "positive")]
+    pub amount: Money,
+    pub memo: String,
+    pub description: Option<String>,
+}
+
+/// A transfer with its two legs. The `from` leg has a negative amount.
+#[derive(Serialize, Debug)]
+pub struct TransferResponse {
+    pub id: i32,
+    pub user_id: Option<i32>,
+    pub amount: Money,
+    pub from: TransactionResponse,
+    pub to: TransactionResponse,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TransferResponse {
+    fn new(
+        transfer: TransferModel,
+        legs: Vec<TransactionModel>,
+        base_currency: &str,
+    ) -> Option<Self> {
+        let [first, second]: [TransactionModel; 2] = legs.try_into().ok()?;
+        let (from, to) = if first.amount < 0 {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let to = TransactionResponse::new(to, base_currency);
+        Some(Self {
+            id: transfer.id,
+            user_id: transfer.user_id,
+            amount: to.amount.clone(),
+            from: TransactionResponse::new(from, base_currency),
+            to,
+            created_at: transfer.created_at,
+        })
+    }
+}
+
+#[get("")]
+pub async fn get_all_transfers(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = get_workspace_access(&req);
+    let base_currency = base_currency(&state.db, access.workspace_id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let transfers: Vec<TransferResponse> = Transfer::find()
+        .filter(transfer::Column::WorkspaceId.eq(access.workspace_id))
+        .order_by_asc(transfer::Column::Id)
+        .find_with_related(Transaction)
+        .all(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .into_iter()
+        .filter(|(_, legs)| allows_legs(&access, legs))
+        .filter_map(|(transfer, legs)| TransferResponse::new(transfer, legs, &base_currency))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(transfers))
+}
+
+/// Moves `amount` from one account and category to another as a pair of
+/// `TRANSFER` legs sharing one journal entry, which are written together or
+/// not at all.
+#[post("")]
+pub async fn create_transfer(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<CreateTransferRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = get_workspace_access(&req);
+
+    if data.from_account_id == data.to_account_id && data.from_category_id == data.to_category_id {
+        return Err(invalid_transfer(
+            "A transfer needs a different account or category on each side",
+        ));
+    }
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let user = User::find_by_id(access.user_id)
+        .one(&txn)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
+    let locked = lock_balances(
+        &txn,
+        &access,
+        &[data.from_account_id, data.to_account_id],
+        &[data.from_category_id, data.to_category_id],
+    )
+    .await?;
+
+    if state.require_verified_email && user.verified_at.is_none() {
+        return Err(actix_web::error::ErrorForbidden(json!({
+            "status": "error",
+            "message": "Verify your email address before creating transactions"
+        })));
+    }
+    let currency = locked.currency(data.from_account_id, data.from_category_id)?;
+    if locked.currency(data.to_account_id, data.to_category_id)? != currency {
+        return Err(invalid_transfer("Both sides must use the same currency"));
+    }
+    if data.amount.currency() != currency {
+        return Err(invalid_transfer(&format!(
+            "amount must be in {}, the currency of the accounts",
+            currency
+        )));
+    }
+    let today = Utc::now().date_naive();
+    let exchange_rate = find_rate(
+        &txn,
+        access.workspace_id,
+        currency,
+        locked.base_currency(),
+        today,
+    )
+    .await?;
+    let base_amount = convert(&data.amount, exchange_rate, locked.base_currency())?;
+
+    let legs = [
+        Leg {
+            account_id: data.from_account_id,
+            category_id: data.from_category_id,
+            delta: -data.amount.minor_units(),
+            base_delta: -base_amount.minor_units(),
+        },
+        Leg {
+            account_id: data.to_account_id,
+            category_id: data.to_category_id,
+            delta: data.amount.minor_units(),
+            base_delta: base_amount.minor_units(),
+        },
+    ];
+    locked.ensure_covered(&legs)?;
+    let entry = record_entry(&txn, &access, &legs).await?;
+
+    let transfer = TransferActiveModel {
+        workspace_id: Set(access.workspace_id),
+        user_id: Set(Some(access.user_id)),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    let mut transactions = Vec::with_capacity(legs.len());
+    for leg in &legs {
+        let transaction = TransactionActiveModel {
+            workspace_id: Set(access.workspace_id),
+            user_id: Set(Some(access.user_id)),
+            account_id: Set(leg.account_id),
+            category_id: Set(leg.category_id),
+            r#type: Set(TransactionKind::Transfer),
+            amount: Set(leg.delta),
+            currency: Set(data.amount.currency().to_string()),
+            exchange_rate: Set(exchange_rate),
+            base_amount: Set(leg.base_delta),
+            date: Set(today),
+            memo: Set(data.memo.clone()),
+            description: Set(data.description.clone()),
+            transfer_id: Set(Some(transfer.id)),
+            journal_entry_id: Set(entry.id),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+        transactions.push(transaction);
+    }
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let response = TransferResponse::new(transfer, transactions, base_amount.currency())
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Transfer legs missing"))?;
+    Ok(HttpResponse::Created().json(response))
+}
+
+#[get("/{id}")]
+pub async fn show_transfer(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = get_workspace_access(&req);
+    let base_currency = base_currency(&state.db, access.workspace_id)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let transfer = Transfer::find_by_id(*id)
+        .filter(transfer::Column::WorkspaceId.eq(access.workspace_id))
+        .find_with_related(Transaction)
+        .all(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .into_iter()
+        .find(|(_, legs)| allows_legs(&access, legs))
+        .and_then(|(transfer, legs)| TransferResponse::new(transfer, legs, &base_currency))
+        .ok_or_else(transfer_not_found)?;
+
+    Ok(HttpResponse::Ok().json(transfer))
+}
+
+#[delete("/{id}")]
+pub async fn delete_transfer(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let access = get_workspace_access(&req);
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    delete_transfer_legs(&txn, &access, *id).await?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+/// Deletes a transfer with both of its legs and reverses their balance
+/// effect. Must run inside a database transaction.
+pub async fn delete_transfer_legs<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
+    transfer_id: i32,
+) -> Result<(), actix_web::Error> {
+    lock_workspace(db, access).await?;
+    let legs = Transaction::find()
+        .filter(transaction::Column::TransferId.eq(transfer_id))
+        .filter(transaction::Column::WorkspaceId.eq(access.workspace_id))
+        .lock_exclusive()
+        .all(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if legs.is_empty() || !allows_legs(access, &legs) {
+        return Err(transfer_not_found());
+    }
+
+    delete_legs(db, access, &legs).await?;
+    let deleted = Transfer::delete_by_id(transfer_id)
+        .exec(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if deleted.rows_affected != 1 {
+        return Err(transfer_not_found());
+    }
+    Ok(())
+}
+
+// Helpers
+
+/// Grants limited to some categories only see transfers between them.
+fn allows_legs(access: &WorkspaceAccess, legs: &[TransactionModel]) -> bool {
+    legs.iter()
+        .all(|leg| access.allows_category(leg.category_id))
+}
+
+fn transfer_not_found() -> actix_web::Error {
+    actix_web::error::ErrorNotFound(
+        json!({"status": "error", "message": "Transfer not found or unauthorized"}),
+    )
+}
+
+fn invalid_transfer(message: &str) -> actix_web::Error {
+    actix_web::error::ErrorBadRequest(json!({"status": "error", "message": message}))
+}
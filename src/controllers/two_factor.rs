@@ -0,0 +1,250 @@
+use actix_web::{HttpRequest, HttpResponse, delete, post, web};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    AppState,
+    entities::recovery_code::{ActiveModel as RecoveryCodeActiveModel, Entity as RecoveryCode},
+    entities::user::{ActiveModel as UserActiveModel, Entity as User, Model as UserModel},
+    utils::auth::get_user_by_id,
+    utils::password::verify_password,
+    utils::responses::unauthorized,
+    utils::totp::{
+        build_totp, generate_recovery_codes, generate_secret, hash_recovery_code,
+        verify_second_factor,
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct TotpCodeRequest {
+    pub code: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DisableTotpRequest {
+    pub password: String,
+}
+
+#[post("/enroll")]
+pub async fn enroll_totp(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+    let user = find_user(&state.db, user_id, false).await?;
+
+    if user.totp_enabled_at.is_some() {
+        return Err(actix_web::error::ErrorConflict(json!({
+            "status": "error",
+            "message": "Two-factor authentication is already enabled"
+        })));
+    }
+
+    let secret = generate_secret();
+    let totp = build_totp(&secret, &user.email)?;
+
+    UserActiveModel {
+        id: Set(user.id),
+        totp_secret: Set(Some(secret.clone())),
+        totp_last_step: Set(None),
+        ..Default::default()
+    }
+    .update(&state.db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "secret": secret,
+        "otpauth_url": totp.get_url()
+    })))
+}
+
+#[post("/confirm")]
+pub async fn confirm_totp(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<TotpCodeRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let user = find_user(&txn, user_id, true).await?;
+    if user.totp_enabled_at.is_some() {
+        return Err(actix_web::error::ErrorConflict(json!({
+            "status": "error",
+            "message": "Two-factor authentication is already enabled"
+        })));
+    }
+    if user.totp_secret.is_none() {
+        return Err(actix_web::error::ErrorBadRequest(json!({
+            "status": "error",
+            "message": "Start the enrollment first"
+        })));
+    }
+    if !verify_second_factor(&txn, &user, Some(&data.code), None).await? {
+        return Err(invalid_code());
+    }
+
+    UserActiveModel {
+        id: Set(user.id),
+        totp_enabled_at: Set(Some(Utc::now())),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .update(&txn)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    let recovery_codes = replace_recovery_codes(&txn, user.id).await?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "recovery_codes": recovery_codes
+    })))
+}
+
+#[post("/recovery-codes")]
+pub async fn regenerate_recovery_codes(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<TotpCodeRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let user = find_user(&txn, user_id, true).await?;
+    if user.totp_enabled_at.is_none() {
+        return Err(not_enabled());
+    }
+    if !verify_second_factor(&txn, &user, Some(&data.code), None).await? {
+        return Err(invalid_code());
+    }
+
+    let recovery_codes = replace_recovery_codes(&txn, user.id).await?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "recovery_codes": recovery_codes
+    })))
+}
+
+#[delete("")]
+pub async fn disable_totp(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<DisableTotpRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let user = find_user(&txn, user_id, true).await?;
+    if user.totp_secret.is_none() {
+        return Err(not_enabled());
+    }
+    if !verify_password(&data.password, &user.password)? {
+        return Err(unauthorized("Invalid password"));
+    }
+
+    UserActiveModel {
+        id: Set(user.id),
+        totp_secret: Set(None),
+        totp_enabled_at: Set(None),
+        totp_last_step: Set(None),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .update(&txn)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    RecoveryCode::delete_many()
+        .filter(crate::entities::recovery_code::Column::UserId.eq(user.id))
+        .exec(&txn)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+// Helpers
+async fn find_user<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    lock: bool,
+) -> Result<UserModel, actix_web::Error> {
+    let mut query = User::find_by_id(user_id);
+    if lock {
+        query = query.lock_exclusive();
+    }
+
+    query
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))
+}
+
+/// Discards the user's recovery codes and stores a fresh batch, returning
+/// the plain codes. They are never retrievable again.
+async fn replace_recovery_codes<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+) -> Result<Vec<String>, actix_web::Error> {
+    RecoveryCode::delete_many()
+        .filter(crate::entities::recovery_code::Column::UserId.eq(user_id))
+        .exec(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let codes = generate_recovery_codes();
+    RecoveryCode::insert_many(codes.iter().map(|code| RecoveryCodeActiveModel {
+        user_id: Set(user_id),
+        code_hash: Set(hash_recovery_code(code)),
+        used_at: Set(None),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }))
+    .exec(db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(codes)
+}
+
+fn invalid_code() -> actix_web::Error {
+    actix_web::error::ErrorBadRequest(json!({"status": "error", "message": "Invalid code"}))
+}
+
+fn not_enabled() -> actix_web::Error {
+    actix_web::error::ErrorBadRequest(json!({
+        "status": "error",
+        "message": "Two-factor authentication is not enabled"
+    }))
+}
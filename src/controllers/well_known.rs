@@ -0,0 +1,10 @@
+use actix_web::{HttpResponse, get, web};
+
+use crate::AppState;
+
+#[get("/.well-known/jwks.json")]
+pub async fn get_jwks(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(state.jwt_keys.jwks())
+}
@@ -0,0 +1,637 @@
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, put, web};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    AppState,
+    entities::sea_orm_active_enums::WorkspaceRole,
+    entities::transaction::{self, Entity as Transaction},
+    entities::user::{Entity as User, Model as UserModel},
+    entities::workspace::{
+        ActiveModel as WorkspaceActiveModel, Entity as Workspace, Model as WorkspaceModel,
+    },
+    entities::workspace_invitation::{
+        self, ActiveModel as WorkspaceInvitationActiveModel, Entity as WorkspaceInvitation,
+    },
+    entities::workspace_member::{
+        self, ActiveModel as WorkspaceMemberActiveModel, Entity as WorkspaceMember,
+        Model as WorkspaceMemberModel,
+    },
+    mailer::Email,
+    utils::auth::get_user_by_id,
+    utils::exchange_rates::validate_currency,
+    utils::money::Money,
+    utils::responses::forbidden,
+    utils::tokens::{generate_token, hash_token},
+    utils::validation::{invalid_request, validate_name},
+    utils::workspaces::{
+        DEFAULT_BASE_CURRENCY, count_owners, create_owned_workspace, find_membership,
+    },
+};
+
+const INVITATION_LIFETIME: Duration = Duration::days(7);
+
+#[derive(Deserialize, Debug)]
+pub struct WorkspaceRequest {
+    pub name: String,
+    pub base_currency: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UpdateMemberRequest {
+    pub role: WorkspaceRole,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InviteRequest {
+    pub email: String,
+    pub role: WorkspaceRole,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct JoinWorkspaceRequest {
+    pub token: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct WorkspaceResponse {
+    pub id: i32,
+    pub name: String,
+    pub balance: Money,
+    pub base_currency: String,
+    pub role: WorkspaceRole,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WorkspaceResponse {
+    fn new(workspace: WorkspaceModel, role: WorkspaceRole) -> Self {
+        Self {
+            id: workspace.id,
+            name: workspace.name,
+            balance: Money::new(workspace.balance, workspace.base_currency.clone()),
+            base_currency: workspace.base_currency,
+            role,
+            created_at: workspace.created_at,
+            updated_at: workspace.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct MemberResponse {
+    pub user_id: i32,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: WorkspaceRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[get("")]
+pub async fn get_all_workspaces(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let workspaces = member_workspaces(&state.db, get_user_by_id(&req)).await?;
+    Ok(HttpResponse::Ok().json(workspaces))
+}
+
+/// Workspaces `user_id` belongs to with their balances, oldest membership
+/// first.
+pub async fn member_workspaces(
+    db: &DatabaseConnection,
+    user_id: i32,
+) -> Result<Vec<WorkspaceResponse>, actix_web::Error> {
+    let memberships = WorkspaceMember::find()
+        .filter(workspace_member::Column::UserId.eq(user_id))
+        .order_by_asc(workspace_member::Column::Id)
+        .find_also_related(Workspace)
+        .all(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(memberships
+        .into_iter()
+        .filter_map(|(member, workspace)| {
+            workspace.map(|workspace| WorkspaceResponse::new(workspace, member.role))
+        })
+        .collect())
+}
+
+#[post("")]
+pub async fn create_workspace(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<WorkspaceRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+    let name = validate_name(&data.name)?;
+    let base_currency = match &data.base_currency {
+        Some(currency) => validate_currency(currency)?,
+        None => DEFAULT_BASE_CURRENCY.to_string(),
+    };
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    let workspace = create_owned_workspace(&txn, user_id, name, &base_currency)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Created().json(WorkspaceResponse::new(workspace, WorkspaceRole::Owner)))
+}
+
+#[get("/{id}")]
+pub async fn show_workspace(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let member = find_member(&state.db, get_user_by_id(&req), *id).await?;
+    let workspace = find_workspace(&state.db, member.workspace_id).await?;
+
+    Ok(HttpResponse::Ok().json(WorkspaceResponse::new(workspace, member.role)))
+}
+
+#[put("/{id}")]
+pub async fn update_workspace(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    data: web::Json<WorkspaceRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let member = find_member(&state.db, get_user_by_id(&req), *id).await?;
+    require_owner(&member)?;
+    let name = validate_name(&data.name)?;
+    let workspace = find_workspace(&state.db, member.workspace_id).await?;
+
+    // Transactions record their rate to the base currency, so it is fixed once
+    // there are any
+    let base_currency = match &data.base_currency {
+        Some(currency) => validate_currency(currency)?,
+        None => workspace.base_currency.clone(),
+    };
+    if base_currency != workspace.base_currency {
+        let transactions = Transaction::find()
+            .filter(transaction::Column::WorkspaceId.eq(workspace.id))
+            .count(&state.db)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+        if transactions > 0 {
+            return Err(actix_web::error::ErrorConflict(json!({
+                "status": "error",
+                "message": "The base currency cannot change once the workspace has transactions"
+            })));
+        }
+    }
+
+    let mut workspace: WorkspaceActiveModel = workspace.into();
+    workspace.name = Set(name.to_string());
+    workspace.base_currency = Set(base_currency);
+    workspace.updated_at = Set(Utc::now());
+    let workspace = workspace.update(&state.db).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(WorkspaceResponse::new(workspace, member.role)))
+}
+
+#[delete("/{id}")]
+pub async fn delete_workspace(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let member = find_member(&state.db, get_user_by_id(&req), *id).await?;
+    require_owner(&member)?;
+
+    // Categories, transactions, memberships and invitations are removed by the
+    // `ON DELETE CASCADE` foreign keys.
+    Workspace::delete_by_id(member.workspace_id)
+        .exec(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+#[get("/{id}/members")]
+pub async fn get_members(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let member = find_member(&state.db, get_user_by_id(&req), *id).await?;
+
+    let members = WorkspaceMember::find()
+        .filter(workspace_member::Column::WorkspaceId.eq(member.workspace_id))
+        .order_by_asc(workspace_member::Column::Id)
+        .find_also_related(User)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let members: Vec<MemberResponse> = members
+        .into_iter()
+        .filter_map(|(member, user)| {
+            user.map(|user| MemberResponse {
+                user_id: user.id,
+                email: user.email,
+                first_name: user.first_name,
+                last_name: user.last_name,
+                role: member.role,
+                joined_at: member.created_at,
+            })
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(members))
+}
+
+#[put("/{id}/members/{user_id}")]
+pub async fn update_member(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(i32, i32)>,
+    data: web::Json<UpdateMemberRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (workspace_id, member_user_id) = path.into_inner();
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let member = find_member(&txn, get_user_by_id(&req), workspace_id).await?;
+    require_owner(&member)?;
+    let target = lock_member(&txn, member_user_id, workspace_id).await?;
+    if target.role == WorkspaceRole::Owner && data.role != WorkspaceRole::Owner {
+        ensure_other_owner(&txn, workspace_id).await?;
+    }
+
+    let mut updated: WorkspaceMemberActiveModel = target.into();
+    updated.role = Set(data.role);
+    let updated = updated.update(&txn).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+/// Removes a member. Owners can remove anyone; every member can remove
+/// themselves to leave the workspace.
+#[delete("/{id}/members/{user_id}")]
+pub async fn remove_member(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (workspace_id, member_user_id) = path.into_inner();
+    let user_id = get_user_by_id(&req);
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let member = find_member(&txn, user_id, workspace_id).await?;
+    if member_user_id != user_id {
+        require_owner(&member)?;
+    }
+    let target = lock_member(&txn, member_user_id, workspace_id).await?;
+    if target.role == WorkspaceRole::Owner {
+        ensure_other_owner(&txn, workspace_id).await?;
+    }
+
+    WorkspaceMember::delete_by_id(target.id)
+        .exec(&txn)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+#[get("/{id}/invitations")]
+pub async fn get_invitations(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let member = find_member(&state.db, get_user_by_id(&req), *id).await?;
+    require_owner(&member)?;
+
+    let invitations = WorkspaceInvitation::find()
+        .filter(workspace_invitation::Column::WorkspaceId.eq(member.workspace_id))
+        .filter(workspace_invitation::Column::AcceptedAt.is_null())
+        .filter(workspace_invitation::Column::ExpiresAt.gt(Utc::now()))
+        .order_by_desc(workspace_invitation::Column::CreatedAt)
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(invitations))
+}
+
+/// Emails an invitation link. A newer invitation for the same address replaces
+/// any pending one.
+#[post("/{id}/invitations")]
+pub async fn invite_member(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    id: web::Path<i32>,
+    data: web::Json<InviteRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = get_user_by_id(&req);
+    let member = find_member(&state.db, user_id, *id).await?;
+    require_owner(&member)?;
+    let workspace = find_workspace(&state.db, member.workspace_id).await?;
+
+    let email = data.email.trim();
+    if email.is_empty() || !email.contains('@') {
+        return Err(invalid_request("A valid email address is required"));
+    }
+
+    let already_member = WorkspaceMember::find()
+        .filter(workspace_member::Column::WorkspaceId.eq(workspace.id))
+        .find_also_related(User)
+        .all(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .into_iter()
+        .any(|(_, user)| user.is_some_and(|user| user.email.eq_ignore_ascii_case(email)));
+    if already_member {
+        return Err(actix_web::error::ErrorConflict(json!({
+            "status": "error",
+            "message": "This person is already a member of the workspace"
+        })));
+    }
+
+    WorkspaceInvitation::delete_many()
+        .filter(workspace_invitation::Column::WorkspaceId.eq(workspace.id))
+        .filter(workspace_invitation::Column::Email.eq(email))
+        .filter(workspace_invitation::Column::AcceptedAt.is_null())
+        .exec(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    let token = generate_token();
+    let invitation = WorkspaceInvitationActiveModel {
+        workspace_id: Set(workspace.id),
+        email: Set(email.to_string()),
+        role: Set(data.role),
+        token_hash: Set(hash_token(&token)),
+        invited_by: Set(Some(user_id)),
+        expires_at: Set(Utc::now() + INVITATION_LIFETIME),
+        accepted_at: Set(None),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    let email = Email {
+        to: invitation.email.clone(),
+        subject: format!("You have been invited to \"{}\" on Spenser", workspace.name),
+        body: format!(
+            "You have been invited to share the \"{}\" budget on Spenser.\n\n\
+             Accept the invitation within the next 7 days with this link:\n\
+             {}/invitations?token={}",
+            workspace.name, state.app_url, token
+        ),
+    };
+    if let Err(e) = state.mailer.send(&email).await {
+        eprintln!("❌ Failed to send workspace invitation: {}", e);
+    }
+
+    Ok(HttpResponse::Created().json(invitation))
+}
+
+#[delete("/{id}/invitations/{invitation_id}")]
+pub async fn revoke_invitation(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (workspace_id, invitation_id) = path.into_inner();
+    let member = find_member(&state.db, get_user_by_id(&req), workspace_id).await?;
+    require_owner(&member)?;
+
+    let deleted = WorkspaceInvitation::delete_many()
+        .filter(workspace_invitation::Column::Id.eq(invitation_id))
+        .filter(workspace_invitation::Column::WorkspaceId.eq(workspace_id))
+        .filter(workspace_invitation::Column::AcceptedAt.is_null())
+        .exec(&state.db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if deleted.rows_affected == 0 {
+        return Err(actix_web::error::ErrorNotFound(
+            json!({"status": "error", "message": "Invitation not found"}),
+        ));
+    }
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+}
+
+/// Accepts an invitation. It must have been sent to the signed-in user's
+/// verified email address.
+#[post("/join")]
+pub async fn join_workspace(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    data: web::Json<JoinWorkspaceRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = User::find_by_id(get_user_by_id(&req))
+        .one(&state.db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
+
+    let txn = state.db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let invitation = WorkspaceInvitation::find()
+        .filter(workspace_invitation::Column::TokenHash.eq(hash_token(&data.token)))
+        .filter(workspace_invitation::Column::AcceptedAt.is_null())
+        .filter(workspace_invitation::Column::ExpiresAt.gt(Utc::now()))
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorBadRequest(json!({
+                "status": "error",
+                "message": "Invalid or expired invitation"
+            }))
+        })?;
+
+    check_invitee(&user, &invitation.email)?;
+    if find_membership(&txn, user.id, Some(invitation.workspace_id))
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .is_some()
+    {
+        return Err(actix_web::error::ErrorConflict(json!({
+            "status": "error",
+            "message": "You are already a member of this workspace"
+        })));
+    }
+
+    WorkspaceMemberActiveModel {
+        workspace_id: Set(invitation.workspace_id),
+        user_id: Set(user.id),
+        role: Set(invitation.role),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    let role = invitation.role;
+    let mut accepted: WorkspaceInvitationActiveModel = invitation.into();
+    accepted.accepted_at = Set(Some(Utc::now()));
+    let accepted = accepted.update(&txn).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let workspace = find_workspace(&txn, accepted.workspace_id).await?;
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(HttpResponse::Ok().json(WorkspaceResponse::new(workspace, role)))
+}
+
+// Helpers
+
+/// Membership of `user_id` in `workspace_id`. Workspaces the user does not
+/// belong to are reported as missing.
+pub async fn find_member<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    workspace_id: i32,
+) -> Result<WorkspaceMemberModel, actix_web::Error> {
+    find_membership(db, user_id, Some(workspace_id))
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(
+                json!({"status": "error", "message": "Workspace not found"}),
+            )
+        })
+}
+
+/// Loads a membership with `SELECT ... FOR UPDATE` before changing it. The
+/// workspace row is locked first, so concurrent role changes cannot both pass
+/// the last-owner check.
+async fn lock_member<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    workspace_id: i32,
+) -> Result<WorkspaceMemberModel, actix_web::Error> {
+    Workspace::find_by_id(workspace_id)
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    WorkspaceMember::find()
+        .filter(workspace_member::Column::WorkspaceId.eq(workspace_id))
+        .filter(workspace_member::Column::UserId.eq(user_id))
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(
+                json!({"status": "error", "message": "Member not found"}),
+            )
+        })
+}
+
+pub async fn find_workspace<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: i32,
+) -> Result<WorkspaceModel, actix_web::Error> {
+    Workspace::find_by_id(workspace_id)
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(
+                json!({"status": "error", "message": "Workspace not found"}),
+            )
+        })
+}
+
+pub fn require_owner(member: &WorkspaceMemberModel) -> Result<(), actix_web::Error> {
+    if member.role != WorkspaceRole::Owner {
+        return Err(forbidden("Only workspace owners can do this"));
+    }
+    Ok(())
+}
+
+/// Every workspace keeps at least one owner.
+async fn ensure_other_owner<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: i32,
+) -> Result<(), actix_web::Error> {
+    let owners = count_owners(db, workspace_id).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+    if owners <= 1 {
+        return Err(actix_web::error::ErrorConflict(json!({
+            "status": "error",
+            "message": "A workspace needs at least one owner. Make another member an owner first."
+        })));
+    }
+    Ok(())
+}
+
+fn check_invitee(user: &UserModel, email: &str) -> Result<(), actix_web::Error> {
+    if !user.email.eq_ignore_ascii_case(email) {
+        return Err(forbidden(
+            "This invitation was sent to a different email address",
+        ));
+    }
+    if user.verified_at.is_none() {
+        return Err(forbidden(
+            "Verify your email address before accepting invitations",
+        ));
+    }
+    Ok(())
+}
@@ -8,16 +8,22 @@ use serde::Serialize;
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
-    pub user_id: i32,
+    pub workspace_id: i32,
+    pub user_id: Option<i32>,
     pub name: String,
     pub description: Option<String>,
     pub balance: i64,
+    pub currency: String,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::access_grant_category::Entity")]
+    AccessGrantCategory,
+    #[sea_orm(has_many = "super::posting::Entity")]
+    Posting,
     #[sea_orm(has_many = "super::transaction::Entity")]
     Transaction,
     #[sea_orm(
@@ -25,9 +31,29 @@ pub enum Relation {
         from = "Column::UserId",
         to = "super::user::Column::Id",
         on_update = "NoAction",
-        on_delete = "Cascade"
+        on_delete = "SetNull"
     )]
     User,
+    #[sea_orm(
+        belongs_to = "super::workspace::Entity",
+        from = "Column::WorkspaceId",
+        to = "super::workspace::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Workspace,
+}
+
+impl Related<super::access_grant_category::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AccessGrantCategory.def()
+    }
+}
+
+impl Related<super::posting::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Posting.def()
+    }
 }
 
 impl Related<super::transaction::Entity> for Entity {
@@ -42,4 +68,10 @@ impl Related<super::user::Entity> for Entity {
     }
 }
 
+impl Related<super::workspace::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Workspace.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
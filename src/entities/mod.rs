@@ -1,7 +1,27 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.7
 
+#[allow(unused_imports)]
 pub mod prelude;
 
+pub mod access_grant;
+pub mod account;
+pub mod access_grant_category;
 pub mod category;
+pub mod exchange_rate;
+pub mod journal_entry;
+pub mod login_attempt;
+pub mod oidc_login_request;
+pub mod one_time_token;
+pub mod personal_access_token;
+pub mod posting;
+pub mod recovery_code;
+pub mod refresh_token;
+pub mod sea_orm_active_enums;
+pub mod session;
 pub mod transaction;
+pub mod transfer;
 pub mod user;
+pub mod user_identity;
+pub mod workspace;
+pub mod workspace_invitation;
+pub mod workspace_member;
@@ -1,5 +1,23 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.7
 
+pub use super::access_grant::Entity as AccessGrant;
+pub use super::access_grant_category::Entity as AccessGrantCategory;
+pub use super::account::Entity as Account;
 pub use super::category::Entity as Category;
+pub use super::exchange_rate::Entity as ExchangeRate;
+pub use super::journal_entry::Entity as JournalEntry;
+pub use super::login_attempt::Entity as LoginAttempt;
+pub use super::oidc_login_request::Entity as OidcLoginRequest;
+pub use super::one_time_token::Entity as OneTimeToken;
+pub use super::personal_access_token::Entity as PersonalAccessToken;
+pub use super::posting::Entity as Posting;
+pub use super::recovery_code::Entity as RecoveryCode;
+pub use super::refresh_token::Entity as RefreshToken;
+pub use super::session::Entity as Session;
 pub use super::transaction::Entity as Transaction;
+pub use super::transfer::Entity as Transfer;
 pub use super::user::Entity as User;
+pub use super::user_identity::Entity as UserIdentity;
+pub use super::workspace::Entity as Workspace;
+pub use super::workspace_invitation::Entity as WorkspaceInvitation;
+pub use super::workspace_member::Entity as WorkspaceMember;
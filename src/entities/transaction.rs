@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.7
 
+use super::sea_orm_active_enums::TransactionKind;
 use sea_orm::entity::prelude::*;
 use serde::Serialize;
 
@@ -8,18 +9,35 @@ use serde::Serialize;
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
-    pub user_id: i32,
+    pub workspace_id: i32,
+    pub user_id: Option<i32>,
+    pub account_id: i32,
     pub category_id: i32,
-    pub r#type: String,
+    pub r#type: TransactionKind,
     pub amount: i64,
+    pub currency: String,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub exchange_rate: Decimal,
+    pub base_amount: i64,
+    pub date: Date,
     pub memo: String,
     pub description: Option<String>,
+    pub transfer_id: Option<i32>,
+    pub journal_entry_id: i32,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::account::Entity",
+        from = "Column::AccountId",
+        to = "super::account::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Account,
     #[sea_orm(
         belongs_to = "super::category::Entity",
         from = "Column::CategoryId",
@@ -28,14 +46,44 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Category,
+    #[sea_orm(
+        belongs_to = "super::journal_entry::Entity",
+        from = "Column::JournalEntryId",
+        to = "super::journal_entry::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    JournalEntry,
+    #[sea_orm(
+        belongs_to = "super::transfer::Entity",
+        from = "Column::TransferId",
+        to = "super::transfer::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Transfer,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
         to = "super::user::Column::Id",
         on_update = "NoAction",
-        on_delete = "Cascade"
+        on_delete = "SetNull"
     )]
     User,
+    #[sea_orm(
+        belongs_to = "super::workspace::Entity",
+        from = "Column::WorkspaceId",
+        to = "super::workspace::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Workspace,
+}
+
+impl Related<super::account::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Account.def()
+    }
 }
 
 impl Related<super::category::Entity> for Entity {
@@ -44,10 +92,28 @@ impl Related<super::category::Entity> for Entity {
     }
 }
 
+impl Related<super::journal_entry::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::JournalEntry.def()
+    }
+}
+
+impl Related<super::transfer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transfer.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
     }
 }
 
+impl Related<super::workspace::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Workspace.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
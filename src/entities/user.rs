@@ -1,8 +1,10 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.7
 
+use super::sea_orm_active_enums::Role;
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize)]
 #[sea_orm(table_name = "user")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -10,18 +12,56 @@ pub struct Model {
     pub first_name: String,
     pub last_name: String,
     pub email: String,
+    #[serde(skip_serializing)]
     pub password: String,
-    pub balance: i64,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
+    pub role: Role,
+    pub disabled_at: Option<DateTimeUtc>,
+    pub verified_at: Option<DateTimeUtc>,
+    #[serde(skip_serializing)]
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<DateTimeUtc>,
+    #[serde(skip_serializing)]
+    pub totp_last_step: Option<i64>,
+    pub failed_login_count: i32,
+    pub locked_until: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::account::Entity")]
+    Account,
     #[sea_orm(has_many = "super::category::Entity")]
     Category,
+    #[sea_orm(has_many = "super::journal_entry::Entity")]
+    JournalEntry,
+    #[sea_orm(has_many = "super::login_attempt::Entity")]
+    LoginAttempt,
+    #[sea_orm(has_many = "super::one_time_token::Entity")]
+    OneTimeToken,
+    #[sea_orm(has_many = "super::personal_access_token::Entity")]
+    PersonalAccessToken,
+    #[sea_orm(has_many = "super::recovery_code::Entity")]
+    RecoveryCode,
+    #[sea_orm(has_many = "super::session::Entity")]
+    Session,
     #[sea_orm(has_many = "super::transaction::Entity")]
     Transaction,
+    #[sea_orm(has_many = "super::transfer::Entity")]
+    Transfer,
+    #[sea_orm(has_many = "super::user_identity::Entity")]
+    UserIdentity,
+    #[sea_orm(has_many = "super::workspace_invitation::Entity")]
+    WorkspaceInvitation,
+    #[sea_orm(has_many = "super::workspace_member::Entity")]
+    WorkspaceMember,
+}
+
+impl Related<super::account::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Account.def()
+    }
 }
 
 impl Related<super::category::Entity> for Entity {
@@ -30,10 +70,70 @@ impl Related<super::category::Entity> for Entity {
     }
 }
 
+impl Related<super::journal_entry::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::JournalEntry.def()
+    }
+}
+
+impl Related<super::login_attempt::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LoginAttempt.def()
+    }
+}
+
+impl Related<super::one_time_token::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OneTimeToken.def()
+    }
+}
+
+impl Related<super::personal_access_token::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PersonalAccessToken.def()
+    }
+}
+
+impl Related<super::recovery_code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecoveryCode.def()
+    }
+}
+
+impl Related<super::session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Session.def()
+    }
+}
+
 impl Related<super::transaction::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Transaction.def()
     }
 }
 
+impl Related<super::transfer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transfer.def()
+    }
+}
+
+impl Related<super::user_identity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserIdentity.def()
+    }
+}
+
+impl Related<super::workspace_invitation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WorkspaceInvitation.def()
+    }
+}
+
+impl Related<super::workspace_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WorkspaceMember.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,30 @@
+pub mod outbox;
+pub mod smtp;
+
+use async_trait::async_trait;
+use std::{env, io, sync::Arc};
+
+use outbox::OutboxMailer;
+use smtp::SmtpMailer;
+
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing mail transport. Selected at startup through the `MAILER`
+/// environment variable.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> io::Result<()>;
+}
+
+/// Builds the mailer configured by `MAILER` (`smtp` or `outbox`, the default).
+pub fn mailer_from_env() -> io::Result<Arc<dyn Mailer>> {
+    match env::var("MAILER").as_deref() {
+        Ok("smtp") => Ok(Arc::new(SmtpMailer::from_env()?)),
+        Ok("outbox") | Err(_) => Ok(Arc::new(OutboxMailer::from_env()?)),
+        Ok(other) => Err(io::Error::other(format!("❌ Unknown MAILER: {}", other))),
+    }
+}
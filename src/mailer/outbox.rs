@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use std::{env, io, path::PathBuf};
+use uuid::Uuid;
+
+use super::{Email, Mailer};
+
+/// Writes every message as a plain-text file into a local directory instead
+/// of sending it. Intended for development and tests.
+pub struct OutboxMailer {
+    dir: PathBuf,
+}
+
+impl OutboxMailer {
+    pub fn from_env() -> io::Result<Self> {
+        let dir = PathBuf::from(env::var("MAIL_OUTBOX_DIR").unwrap_or_else(|_| "outbox".into()));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+}
+
+#[async_trait]
+impl Mailer for OutboxMailer {
+    async fn send(&self, email: &Email) -> io::Result<()> {
+        let now = Utc::now();
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            now.format("%Y%m%dT%H%M%S"),
+            Uuid::new_v4()
+        ));
+        let contents = format!(
+            "To: {}\r\nSubject: {}\r\nDate: {}\r\n\r\n{}\r\n",
+            email.to,
+            email.subject,
+            now.to_rfc2822(),
+            email.body
+        );
+
+        tokio::fs::write(&path, contents).await?;
+        println!("✉️  Mail to {} written to {}", email.to, path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use std::{env, io};
+
+use super::{Email, Mailer};
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// Reads `SMTP_HOST`, `SMTP_PORT`, `SMTP_USERNAME`, `SMTP_PASSWORD`,
+    /// `SMTP_TLS` (`tls`, `starttls` or `none`) and `MAIL_FROM`.
+    pub fn from_env() -> io::Result<Self> {
+        let host = env::var("SMTP_HOST").map_err(|_| io::Error::other("❌ SMTP_HOST not set"))?;
+        let from = env::var("MAIL_FROM")
+            .map_err(|_| io::Error::other("❌ MAIL_FROM not set"))?
+            .parse::<Mailbox>()
+            .map_err(|e| io::Error::other(format!("❌ Invalid MAIL_FROM: {}", e)))?;
+
+        let mut builder = match env::var("SMTP_TLS").as_deref() {
+            Ok("none") => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+            Ok("starttls") => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+                .map_err(|e| io::Error::other(format!("❌ Invalid SMTP_HOST: {}", e)))?,
+            _ => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)
+                .map_err(|e| io::Error::other(format!("❌ Invalid SMTP_HOST: {}", e)))?,
+        };
+        if let Some(port) = env::var("SMTP_PORT").ok().filter(|port| !port.is_empty()) {
+            let port = port
+                .parse()
+                .map_err(|_| io::Error::other("❌ SMTP_PORT must be a number"))?;
+            builder = builder.port(port);
+        }
+        if let (Ok(username), Ok(password)) = (env::var("SMTP_USERNAME"), env::var("SMTP_PASSWORD"))
+            && !username.is_empty()
+        {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> io::Result<()> {
+        let to = email
+            .to
+            .parse::<Mailbox>()
+            .map_err(|e| io::Error::other(format!("Invalid recipient: {}", e)))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body.clone())
+            .map_err(|e| io::Error::other(format!("Invalid message: {}", e)))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| io::Error::other(format!("SMTP error: {}", e)))?;
+        Ok(())
+    }
+}
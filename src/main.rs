@@ -1,31 +1,63 @@
+mod cli;
 mod controllers;
 mod entities;
+mod mailer;
 mod middlewares;
 mod migrations;
 pub mod routes;
 mod utils;
 
 use actix_web::{App, HttpServer, web::Data};
+use mailer::{Mailer, mailer_from_env};
 use sea_orm::DatabaseConnection;
-use std::{env, io::Result};
+use std::{env, io::Result, sync::Arc};
+use utils::cookies::CookiePolicy;
 use utils::db::establish_connection;
 use utils::db::run_migrations;
+use utils::jwt_keys::KeyRing;
+use utils::login_attempts::LoginPolicy;
+use utils::oidc::OidcConfig;
+use utils::password::PasswordPolicy;
 
 struct AppState {
     db: DatabaseConnection,
-    jwt_secret: String,
+    jwt_keys: KeyRing,
+    mailer: Arc<dyn Mailer>,
+    app_url: String,
+    require_verified_email: bool,
+    login_policy: LoginPolicy,
+    password_policy: PasswordPolicy,
+    oidc: Option<OidcConfig>,
+    cookies: Option<CookiePolicy>,
 }
 
 #[actix_web::main]
 async fn main() -> Result<()> {
     let pool = establish_connection().await?;
+    run_migrations(&pool).await?;
+
+    // Offline commands only need the database, not the keys, mailer and
+    // sign-in settings the server is configured with.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if cli::run(&pool, &args).await? {
+        return Ok(());
+    }
+
+    let app_url = env::var("APP_URL").unwrap_or_else(|_| "http://localhost:8005".to_string());
     let app_state = Data::new(AppState {
-        jwt_secret: env::var("JWT_SECRET").unwrap(),
+        jwt_keys: KeyRing::from_env()?,
         db: pool,
+        mailer: mailer_from_env()?,
+        oidc: OidcConfig::from_env(&app_url)?,
+        app_url,
+        require_verified_email: env::var("REQUIRE_VERIFIED_EMAIL")
+            .map(|value| value != "false")
+            .unwrap_or(true),
+        login_policy: LoginPolicy::from_env(),
+        password_policy: PasswordPolicy::from_env()?,
+        cookies: CookiePolicy::from_env(),
     });
 
-    run_migrations(&app_state.db).await?;
-
     HttpServer::new(move || {
         App::new()
             .app_data(app_state.clone())
@@ -1,48 +1,141 @@
+use actix_web::body::BoxBody;
 use actix_web::{
     Error, HttpMessage,
     dev::{ServiceRequest, ServiceResponse},
     middleware::Next,
     web,
 };
-use actix_web::body::BoxBody;
-use jsonwebtoken::{DecodingKey, Validation, decode};
+use futures_util::future::LocalBoxFuture;
 
-use crate::utils::responses::unauthorized;
-use crate::{AppState, controllers::auth::Claims};
+use crate::AppState;
+use crate::controllers::auth::Claims;
+use crate::entities::sea_orm_active_enums::Role;
+use crate::utils::cookies::{ACCESS_COOKIE, check_csrf};
+use crate::utils::jwt::validate_access_token;
+use crate::utils::personal_access_tokens::{
+    TOKEN_PREFIX, TokenScopes, authenticate_personal_access_token,
+};
+use crate::utils::responses::{forbidden, unauthorized};
+use crate::utils::sessions::touch_session;
 
 pub async fn verify_jwt(
     req: ServiceRequest,
     next: Next<BoxBody>,
 ) -> Result<ServiceResponse, Error> {
-    let token = extract_token(&req)?;
     let state = req
         .app_data::<web::Data<AppState>>()
         .ok_or_else(|| unauthorized("Missing app state"))?;
-    let claims = validate_token(token, &state.jwt_secret)?;
+    let (token, from_cookie) = extract_token(&req, state.cookies.is_some())?;
+
+    // Browsers attach cookies to cross-site requests too, so state-changing
+    // requests must prove they can read the CSRF cookie
+    if from_cookie && !req.method().is_safe() {
+        check_csrf(req.request())?;
+    }
+
+    if token.starts_with(TOKEN_PREFIX) {
+        let (user_id, scopes) = authenticate_personal_access_token(&state.db, &token).await?;
+        req.extensions_mut().insert(user_id);
+        req.extensions_mut().insert(scopes);
+        return next.call(req).await;
+    }
+
+    let claims = validate_access_token(&token, &state.jwt_keys)?;
+
+    let active = touch_session(&state.db, &claims.sid, &claims.jti)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if !active {
+        return Err(unauthorized(
+            "Session has been revoked or the token replaced",
+        ));
+    }
 
     req.extensions_mut().insert(claims.sub);
+    req.extensions_mut().insert(claims);
     next.call(req).await
 }
 
-fn extract_token(req: &ServiceRequest) -> Result<&str, Error> {
-    let auth_header = req
-        .headers()
-        .get("Authorization")
-        .ok_or_else(|| unauthorized("Authorization header is missing"))?
+/// Restricts a scope to callers whose token carries `role`. Admins satisfy
+/// every role requirement. Must be wrapped inside `verify_jwt`.
+pub fn require_role(
+    role: Role,
+) -> impl Fn(ServiceRequest, Next<BoxBody>) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>>
+{
+    move |req, next| {
+        Box::pin(async move {
+            let allowed = req
+                .extensions()
+                .get::<Claims>()
+                .is_some_and(|claims| claims.role == role || claims.role == Role::Admin);
+            if !allowed {
+                return Err(forbidden("Insufficient permissions"));
+            }
+            next.call(req).await
+        })
+    }
+}
+
+/// Limits personal access tokens to the scopes they were granted for
+/// `resource`: safe methods need `<resource>:read`, everything else
+/// `<resource>:write`. Session tokens pass through. Must be wrapped inside
+/// `verify_jwt`.
+pub fn require_scope(
+    resource: &'static str,
+) -> impl Fn(ServiceRequest, Next<BoxBody>) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>>
+{
+    move |req, next| {
+        Box::pin(async move {
+            let access = if req.method().is_safe() {
+                "read"
+            } else {
+                "write"
+            };
+            let allowed = req
+                .extensions()
+                .get::<TokenScopes>()
+                .is_none_or(|scopes| scopes.allows(resource, access));
+            if !allowed {
+                return Err(forbidden(&format!(
+                    "Access token is missing the '{}:{}' scope",
+                    resource, access
+                )));
+            }
+            next.call(req).await
+        })
+    }
+}
+
+/// Rejects personal access tokens, for routes that manage the account itself.
+/// Must be wrapped inside `verify_jwt`.
+pub async fn require_session(
+    req: ServiceRequest,
+    next: Next<BoxBody>,
+) -> Result<ServiceResponse, Error> {
+    if req.extensions().get::<TokenScopes>().is_some() {
+        return Err(forbidden("Personal access tokens cannot be used here"));
+    }
+    next.call(req).await
+}
+
+/// Returns the access token and whether it came from the access cookie. The
+/// Authorization header takes precedence; the cookie is only read when cookie
+/// mode is enabled.
+fn extract_token(req: &ServiceRequest, cookies_enabled: bool) -> Result<(String, bool), Error> {
+    let Some(auth_header) = req.headers().get("Authorization") else {
+        if let Some(cookie) = req.cookie(ACCESS_COOKIE).filter(|_| cookies_enabled) {
+            return Ok((cookie.value().to_string(), true));
+        }
+        return Err(unauthorized("Authorization header is missing"));
+    };
+    let auth_header = auth_header
         .to_str()
         .map_err(|_| unauthorized("Authorization header is malformed"))?;
 
     auth_header
         .strip_prefix("Bearer ")
+        .map(|token| (token.to_string(), false))
         .ok_or_else(|| unauthorized("Authorization header must start with 'Bearer '"))
 }
-
-fn validate_token(token: &str, secret: &str) -> Result<Claims, Error> {
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
-    )
-    .map(|token_data| token_data.claims)
-    .map_err(|_| unauthorized("Invalid token"))
-}
@@ -0,0 +1,80 @@
+use actix_web::body::BoxBody;
+use actix_web::{
+    Error, HttpMessage,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web,
+};
+use serde_json::json;
+
+use crate::AppState;
+use crate::utils::access_grants::{ACCESS_GRANT_HEADER, resolve_access_grant};
+use crate::utils::responses::{forbidden, unauthorized};
+use crate::utils::workspaces::{WORKSPACE_HEADER, WorkspaceAccess, find_membership};
+
+/// Resolves the workspace the request acts in and stores the caller's
+/// `WorkspaceAccess` in the request extensions. An `X-Access-Grant` header
+/// switches to a workspace shared through an access grant; otherwise the
+/// `X-Workspace-Id` header, or the caller's default workspace, is used.
+/// Read-only access is limited to safe methods. Must be wrapped inside
+/// `verify_jwt`.
+pub async fn resolve_workspace(
+    req: ServiceRequest,
+    next: Next<BoxBody>,
+) -> Result<ServiceResponse, Error> {
+    let state = req
+        .app_data::<web::Data<AppState>>()
+        .ok_or_else(|| unauthorized("Missing app state"))?;
+    let user_id = req
+        .extensions()
+        .get::<i32>()
+        .copied()
+        .ok_or_else(|| unauthorized("Missing user"))?;
+
+    let access = match id_header(&req, ACCESS_GRANT_HEADER)? {
+        Some(grant_id) => resolve_access_grant(&state.db, grant_id, user_id)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?
+            .ok_or_else(|| forbidden("Access grant is invalid, revoked or expired"))?,
+        None => {
+            let workspace_id = id_header(&req, WORKSPACE_HEADER)?;
+            let member = find_membership(&state.db, user_id, workspace_id)
+                .await
+                .map_err(|e| {
+                    actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+                })?
+                .ok_or_else(|| {
+                    actix_web::error::ErrorNotFound(
+                        json!({"status": "error", "message": "Workspace not found"}),
+                    )
+                })?;
+            WorkspaceAccess::from_membership(&member)
+        }
+    };
+
+    if !req.method().is_safe() && !access.can_write {
+        return Err(forbidden("You have read-only access to this workspace"));
+    }
+
+    req.extensions_mut().insert(access);
+    next.call(req).await
+}
+
+fn id_header(req: &ServiceRequest, name: &str) -> Result<Option<i32>, Error> {
+    let Some(value) = req.headers().get(name) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .and_then(|value| value.parse::<i32>().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            actix_web::error::ErrorBadRequest(json!({
+                "status": "error",
+                "message": format!("{} must be a numeric id", name)
+            }))
+        })
+}
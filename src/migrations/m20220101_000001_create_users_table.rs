@@ -1,5 +1,4 @@
 use sea_orm_migration::{prelude::*, schema::*};
-use crate::migrations::m20250323_095154_create_categories_table::Category;
 
 #[derive(DeriveMigrationName)]
 pub struct Migration;
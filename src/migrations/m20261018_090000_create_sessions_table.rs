@@ -0,0 +1,89 @@
+use super::m20220101_000001_create_users_table::User;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Session::Table)
+                    .if_not_exists()
+                    .col(char_len(Session::Id, 36).primary_key())
+                    .col(integer(Session::UserId).not_null())
+                    .col(
+                        timestamp(Session::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .col(timestamp_null(Session::RevokedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_user_id")
+                            .from(Session::Table, Session::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RefreshToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RefreshToken::Id))
+                    .col(char_len(RefreshToken::SessionId, 36).not_null())
+                    .col(char_len_uniq(RefreshToken::TokenHash, 64).not_null())
+                    .col(timestamp(RefreshToken::ExpiresAt).not_null())
+                    .col(timestamp_null(RefreshToken::UsedAt))
+                    .col(
+                        timestamp(RefreshToken::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_refresh_token_session_id")
+                            .from(RefreshToken::Table, RefreshToken::SessionId)
+                            .to(Session::Table, Session::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RefreshToken::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Session::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Session {
+    Table,
+    Id,
+    UserId,
+    CreatedAt,
+    RevokedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum RefreshToken {
+    Table,
+    Id,
+    SessionId,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
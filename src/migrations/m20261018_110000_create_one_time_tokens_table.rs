@@ -0,0 +1,55 @@
+use super::m20220101_000001_create_users_table::User;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OneTimeToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(OneTimeToken::Id))
+                    .col(integer(OneTimeToken::UserId).not_null())
+                    .col(string_len(OneTimeToken::Purpose, 32).not_null())
+                    .col(char_len_uniq(OneTimeToken::TokenHash, 64).not_null())
+                    .col(timestamp(OneTimeToken::ExpiresAt).not_null())
+                    .col(timestamp_null(OneTimeToken::UsedAt))
+                    .col(
+                        timestamp(OneTimeToken::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_one_time_token_user_id")
+                            .from(OneTimeToken::Table, OneTimeToken::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OneTimeToken::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum OneTimeToken {
+    Table,
+    Id,
+    UserId,
+    Purpose,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,45 @@
+use super::m20220101_000001_create_users_table::User;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(timestamp_null(UserVerification::VerifiedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Accounts created before verification existed are trusted as-is
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(User::Table)
+                    .value(UserVerification::VerifiedAt, Expr::col(User::CreatedAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(UserVerification::VerifiedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum UserVerification {
+    VerifiedAt,
+}
@@ -0,0 +1,80 @@
+use super::m20220101_000001_create_users_table::User;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(string_null(UserTotp::TotpSecret))
+                    .add_column(timestamp_null(UserTotp::TotpEnabledAt))
+                    .add_column(big_integer_null(UserTotp::TotpLastStep))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RecoveryCode::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RecoveryCode::Id))
+                    .col(integer(RecoveryCode::UserId).not_null())
+                    .col(char_len(RecoveryCode::CodeHash, 64).not_null())
+                    .col(timestamp_null(RecoveryCode::UsedAt))
+                    .col(
+                        timestamp(RecoveryCode::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_recovery_code_user_id")
+                            .from(RecoveryCode::Table, RecoveryCode::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RecoveryCode::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(UserTotp::TotpSecret)
+                    .drop_column(UserTotp::TotpEnabledAt)
+                    .drop_column(UserTotp::TotpLastStep)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(clippy::enum_variant_names)]
+pub enum UserTotp {
+    TotpSecret,
+    TotpEnabledAt,
+    TotpLastStep,
+}
+
+#[derive(DeriveIden)]
+pub enum RecoveryCode {
+    Table,
+    Id,
+    UserId,
+    CodeHash,
+    UsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,99 @@
+use super::m20220101_000001_create_users_table::User;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginAttempt::Table)
+                    .if_not_exists()
+                    .col(pk_auto(LoginAttempt::Id))
+                    .col(integer_null(LoginAttempt::UserId))
+                    .col(string(LoginAttempt::Email).not_null())
+                    .col(string_len(LoginAttempt::Ip, 45).not_null())
+                    .col(boolean(LoginAttempt::Succeeded).not_null())
+                    .col(
+                        timestamp(LoginAttempt::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_login_attempt_user_id")
+                            .from(LoginAttempt::Table, LoginAttempt::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_attempt_email_created_at")
+                    .table(LoginAttempt::Table)
+                    .col(LoginAttempt::Email)
+                    .col(LoginAttempt::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_attempt_ip_created_at")
+                    .table(LoginAttempt::Table)
+                    .col(LoginAttempt::Ip)
+                    .col(LoginAttempt::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(integer(UserLockout::FailedLoginCount).default(0).not_null())
+                    .add_column(timestamp_null(UserLockout::LockedUntil))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(UserLockout::FailedLoginCount)
+                    .drop_column(UserLockout::LockedUntil)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(LoginAttempt::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum LoginAttempt {
+    Table,
+    Id,
+    UserId,
+    Email,
+    Ip,
+    Succeeded,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum UserLockout {
+    FailedLoginCount,
+    LockedUntil,
+}
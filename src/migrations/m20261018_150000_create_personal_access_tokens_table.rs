@@ -0,0 +1,61 @@
+use super::m20220101_000001_create_users_table::User;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PersonalAccessToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PersonalAccessToken::Id))
+                    .col(integer(PersonalAccessToken::UserId).not_null())
+                    .col(string(PersonalAccessToken::Name).not_null())
+                    .col(string_len(PersonalAccessToken::TokenPrefix, 16).not_null())
+                    .col(char_len_uniq(PersonalAccessToken::TokenHash, 64).not_null())
+                    .col(string(PersonalAccessToken::Scopes).not_null())
+                    .col(timestamp_null(PersonalAccessToken::ExpiresAt))
+                    .col(timestamp_null(PersonalAccessToken::LastUsedAt))
+                    .col(timestamp_null(PersonalAccessToken::RevokedAt))
+                    .col(
+                        timestamp(PersonalAccessToken::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_personal_access_token_user_id")
+                            .from(PersonalAccessToken::Table, PersonalAccessToken::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PersonalAccessToken::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum PersonalAccessToken {
+    Table,
+    Id,
+    UserId,
+    Name,
+    TokenPrefix,
+    TokenHash,
+    Scopes,
+    ExpiresAt,
+    LastUsedAt,
+    RevokedAt,
+    CreatedAt,
+}
@@ -0,0 +1,44 @@
+use super::m20261018_090000_create_sessions_table::Session;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .add_column(string_null(SessionClient::UserAgent))
+                    .add_column(string_len_null(SessionClient::Ip, 45))
+                    .add_column(timestamp_null(SessionClient::LastSeenAt))
+                    .add_column(char_len_null(SessionClient::AccessTokenJti, 36))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .drop_column(SessionClient::UserAgent)
+                    .drop_column(SessionClient::Ip)
+                    .drop_column(SessionClient::LastSeenAt)
+                    .drop_column(SessionClient::AccessTokenJti)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum SessionClient {
+    UserAgent,
+    Ip,
+    LastSeenAt,
+    AccessTokenJti,
+}
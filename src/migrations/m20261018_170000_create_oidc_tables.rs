@@ -0,0 +1,100 @@
+use super::m20220101_000001_create_users_table::User;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserIdentity::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserIdentity::Id))
+                    .col(integer(UserIdentity::UserId).not_null())
+                    .col(string(UserIdentity::Issuer).not_null())
+                    .col(string(UserIdentity::Subject).not_null())
+                    .col(string_null(UserIdentity::Email))
+                    .col(timestamp_null(UserIdentity::LastLoginAt))
+                    .col(
+                        timestamp(UserIdentity::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_user_identity_user_id")
+                            .from(UserIdentity::Table, UserIdentity::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_identity_issuer_subject")
+                    .table(UserIdentity::Table)
+                    .col(UserIdentity::Issuer)
+                    .col(UserIdentity::Subject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(OidcLoginRequest::Table)
+                    .if_not_exists()
+                    .col(pk_auto(OidcLoginRequest::Id))
+                    .col(char_len_uniq(OidcLoginRequest::StateHash, 64).not_null())
+                    .col(string(OidcLoginRequest::Nonce).not_null())
+                    .col(string(OidcLoginRequest::CodeVerifier).not_null())
+                    .col(timestamp(OidcLoginRequest::ExpiresAt).not_null())
+                    .col(
+                        timestamp(OidcLoginRequest::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OidcLoginRequest::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(UserIdentity::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum UserIdentity {
+    Table,
+    Id,
+    UserId,
+    Issuer,
+    Subject,
+    Email,
+    LastLoginAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum OidcLoginRequest {
+    Table,
+    Id,
+    StateHash,
+    Nonce,
+    CodeVerifier,
+    ExpiresAt,
+    CreatedAt,
+}
@@ -0,0 +1,364 @@
+use super::m20220101_000001_create_users_table::User;
+use super::m20250323_095154_create_categories_table::Category;
+use super::m20250323_101235_create_transactions_table::Transaction;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Workspace::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Workspace::Id))
+                    .col(string(Workspace::Name).not_null())
+                    .col(big_integer(Workspace::Balance).default(0))
+                    .col(
+                        timestamp(Workspace::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .col(
+                        timestamp(Workspace::UpdatedAt)
+                            .default(Expr::current_timestamp())
+                            .extra("ON UPDATE CURRENT_TIMESTAMP")
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WorkspaceMember::Table)
+                    .if_not_exists()
+                    .col(pk_auto(WorkspaceMember::Id))
+                    .col(integer(WorkspaceMember::WorkspaceId).not_null())
+                    .col(integer(WorkspaceMember::UserId).not_null())
+                    .col(string_len(WorkspaceMember::Role, 16).not_null())
+                    .col(
+                        timestamp(WorkspaceMember::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_workspace_member_workspace_id")
+                            .from(WorkspaceMember::Table, WorkspaceMember::WorkspaceId)
+                            .to(Workspace::Table, Workspace::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_workspace_member_user_id")
+                            .from(WorkspaceMember::Table, WorkspaceMember::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_workspace_member_workspace_user")
+                    .table(WorkspaceMember::Table)
+                    .col(WorkspaceMember::WorkspaceId)
+                    .col(WorkspaceMember::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WorkspaceInvitation::Table)
+                    .if_not_exists()
+                    .col(pk_auto(WorkspaceInvitation::Id))
+                    .col(integer(WorkspaceInvitation::WorkspaceId).not_null())
+                    .col(string(WorkspaceInvitation::Email).not_null())
+                    .col(string_len(WorkspaceInvitation::Role, 16).not_null())
+                    .col(char_len_uniq(WorkspaceInvitation::TokenHash, 64).not_null())
+                    .col(integer_null(WorkspaceInvitation::InvitedBy))
+                    .col(timestamp(WorkspaceInvitation::ExpiresAt).not_null())
+                    .col(timestamp_null(WorkspaceInvitation::AcceptedAt))
+                    .col(
+                        timestamp(WorkspaceInvitation::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_workspace_invitation_workspace_id")
+                            .from(WorkspaceInvitation::Table, WorkspaceInvitation::WorkspaceId)
+                            .to(Workspace::Table, Workspace::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_workspace_invitation_invited_by")
+                            .from(WorkspaceInvitation::Table, WorkspaceInvitation::InvitedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every existing user gets a personal workspace that takes over their
+        // balance. Reusing the user id as workspace id keeps the backfill of
+        // categories and transactions a plain copy.
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Workspace::Table)
+                    .columns([
+                        Workspace::Id,
+                        Workspace::Name,
+                        Workspace::Balance,
+                        Workspace::CreatedAt,
+                        Workspace::UpdatedAt,
+                    ])
+                    .select_from(
+                        Query::select()
+                            .column(User::Id)
+                            .expr(Expr::val("Personal"))
+                            .column(User::Balance)
+                            .expr(Expr::current_timestamp())
+                            .expr(Expr::current_timestamp())
+                            .from(User::Table)
+                            .to_owned(),
+                    )
+                    .map_err(|e| DbErr::Migration(e.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(WorkspaceMember::Table)
+                    .columns([
+                        WorkspaceMember::WorkspaceId,
+                        WorkspaceMember::UserId,
+                        WorkspaceMember::Role,
+                        WorkspaceMember::CreatedAt,
+                    ])
+                    .select_from(
+                        Query::select()
+                            .column(User::Id)
+                            .column(User::Id)
+                            .expr(Expr::val("owner"))
+                            .expr(Expr::current_timestamp())
+                            .from(User::Table)
+                            .to_owned(),
+                    )
+                    .map_err(|e| DbErr::Migration(e.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        move_to_workspaces(
+            manager,
+            Category::Table.into_iden(),
+            "fk_category_user_id",
+            "fk_category_workspace_id",
+        )
+        .await?;
+        move_to_workspaces(
+            manager,
+            Transaction::Table.into_iden(),
+            "fk_transactions_user_id",
+            "fk_transactions_workspace_id",
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::Balance)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(big_integer(User::Balance).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Rows whose creator has deleted their account cannot go back to the
+        // per-user model
+        for (table, user_fk, workspace_fk) in [
+            (
+                Transaction::Table.into_iden(),
+                "fk_transactions_user_id",
+                "fk_transactions_workspace_id",
+            ),
+            (
+                Category::Table.into_iden(),
+                "fk_category_user_id",
+                "fk_category_workspace_id",
+            ),
+        ] {
+            manager
+                .exec_stmt(
+                    Query::delete()
+                        .from_table(table.clone())
+                        .and_where(Expr::col(WorkspaceScoped::UserId).is_null())
+                        .to_owned(),
+                )
+                .await?;
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table.clone())
+                        .drop_foreign_key(Alias::new(workspace_fk))
+                        .drop_foreign_key(Alias::new(user_fk))
+                        .to_owned(),
+                )
+                .await?;
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table.clone())
+                        .drop_column(WorkspaceScoped::WorkspaceId)
+                        .modify_column(integer(WorkspaceScoped::UserId).not_null())
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name(user_fk)
+                                .from_tbl(table.clone())
+                                .from_col(WorkspaceScoped::UserId)
+                                .to_tbl(User::Table)
+                                .to_col(User::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(WorkspaceInvitation::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(WorkspaceMember::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Workspace::Table).to_owned())
+            .await
+    }
+}
+
+/// Adds a `workspace_id` filled from the personal workspaces, and turns
+/// `user_id` into a nullable creator reference so shared rows survive when a
+/// member deletes their account.
+async fn move_to_workspaces(
+    manager: &SchemaManager<'_>,
+    table: DynIden,
+    user_fk: &str,
+    workspace_fk: &str,
+) -> Result<(), DbErr> {
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table.clone())
+                .add_column(integer_null(WorkspaceScoped::WorkspaceId))
+                .drop_foreign_key(Alias::new(user_fk))
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .exec_stmt(
+            Query::update()
+                .table(table.clone())
+                .value(
+                    WorkspaceScoped::WorkspaceId,
+                    Expr::col(WorkspaceScoped::UserId),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table.clone())
+                .modify_column(integer(WorkspaceScoped::WorkspaceId).not_null())
+                .modify_column(integer_null(WorkspaceScoped::UserId))
+                .add_foreign_key(
+                    TableForeignKey::new()
+                        .name(workspace_fk)
+                        .from_tbl(table.clone())
+                        .from_col(WorkspaceScoped::WorkspaceId)
+                        .to_tbl(Workspace::Table)
+                        .to_col(Workspace::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .add_foreign_key(
+                    TableForeignKey::new()
+                        .name(user_fk)
+                        .from_tbl(table)
+                        .from_col(WorkspaceScoped::UserId)
+                        .to_tbl(User::Table)
+                        .to_col(User::Id)
+                        .on_delete(ForeignKeyAction::SetNull),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+#[derive(DeriveIden)]
+pub enum Workspace {
+    Table,
+    Id,
+    Name,
+    Balance,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum WorkspaceMember {
+    Table,
+    Id,
+    WorkspaceId,
+    UserId,
+    Role,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum WorkspaceInvitation {
+    Table,
+    Id,
+    WorkspaceId,
+    Email,
+    Role,
+    TokenHash,
+    InvitedBy,
+    ExpiresAt,
+    AcceptedAt,
+    CreatedAt,
+}
+
+/// Columns shared by the `category` and `transaction` tables.
+#[derive(DeriveIden)]
+pub enum WorkspaceScoped {
+    WorkspaceId,
+    UserId,
+}
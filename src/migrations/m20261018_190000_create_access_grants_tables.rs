@@ -0,0 +1,118 @@
+use super::m20220101_000001_create_users_table::User;
+use super::m20250323_095154_create_categories_table::Category;
+use super::m20261018_180000_create_workspaces_tables::Workspace;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessGrant::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AccessGrant::Id))
+                    .col(integer(AccessGrant::WorkspaceId).not_null())
+                    .col(integer_null(AccessGrant::GrantorId))
+                    .col(integer(AccessGrant::GranteeId).not_null())
+                    .col(string_len(AccessGrant::Access, 16).not_null())
+                    .col(boolean(AccessGrant::AllCategories).not_null())
+                    .col(timestamp(AccessGrant::ExpiresAt).not_null())
+                    .col(timestamp_null(AccessGrant::RevokedAt))
+                    .col(
+                        timestamp(AccessGrant::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_access_grant_workspace_id")
+                            .from(AccessGrant::Table, AccessGrant::WorkspaceId)
+                            .to(Workspace::Table, Workspace::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_access_grant_grantor_id")
+                            .from(AccessGrant::Table, AccessGrant::GrantorId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_access_grant_grantee_id")
+                            .from(AccessGrant::Table, AccessGrant::GranteeId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessGrantCategory::Table)
+                    .if_not_exists()
+                    .col(integer(AccessGrantCategory::AccessGrantId).not_null())
+                    .col(integer(AccessGrantCategory::CategoryId).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(AccessGrantCategory::AccessGrantId)
+                            .col(AccessGrantCategory::CategoryId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_access_grant_category_access_grant_id")
+                            .from(
+                                AccessGrantCategory::Table,
+                                AccessGrantCategory::AccessGrantId,
+                            )
+                            .to(AccessGrant::Table, AccessGrant::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_access_grant_category_category_id")
+                            .from(AccessGrantCategory::Table, AccessGrantCategory::CategoryId)
+                            .to(Category::Table, Category::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessGrantCategory::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(AccessGrant::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AccessGrant {
+    Table,
+    Id,
+    WorkspaceId,
+    GrantorId,
+    GranteeId,
+    Access,
+    AllCategories,
+    ExpiresAt,
+    RevokedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum AccessGrantCategory {
+    Table,
+    AccessGrantId,
+    CategoryId,
+}
@@ -0,0 +1,172 @@
+use super::m20220101_000001_create_users_table::User;
+use super::m20250323_101235_create_transactions_table::Transaction;
+use super::m20261018_180000_create_workspaces_tables::Workspace;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Account::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Account::Id))
+                    .col(integer(Account::WorkspaceId).not_null())
+                    .col(integer_null(Account::UserId))
+                    .col(string(Account::Name).not_null())
+                    .col(string_len(Account::Kind, 16).not_null())
+                    .col(char_len(Account::Currency, 3).not_null())
+                    .col(big_integer(Account::Balance).default(0))
+                    .col(
+                        timestamp(Account::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .col(
+                        timestamp(Account::UpdatedAt)
+                            .default(Expr::current_timestamp())
+                            .extra("ON UPDATE CURRENT_TIMESTAMP")
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_account_workspace_id")
+                            .from(Account::Table, Account::WorkspaceId)
+                            .to(Workspace::Table, Workspace::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_account_user_id")
+                            .from(Account::Table, Account::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every workspace gets a "Main" account that takes over its balance.
+        // Reusing the workspace id as account id keeps the backfill of
+        // transactions a plain copy.
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Account::Table)
+                    .columns([
+                        Account::Id,
+                        Account::WorkspaceId,
+                        Account::Name,
+                        Account::Kind,
+                        Account::Currency,
+                        Account::Balance,
+                        Account::CreatedAt,
+                        Account::UpdatedAt,
+                    ])
+                    .select_from(
+                        Query::select()
+                            .column(Workspace::Id)
+                            .column(Workspace::Id)
+                            .expr(Expr::val("Main"))
+                            .expr(Expr::val("checking"))
+                            .expr(Expr::val("USD"))
+                            .column(Workspace::Balance)
+                            .expr(Expr::current_timestamp())
+                            .expr(Expr::current_timestamp())
+                            .from(Workspace::Table)
+                            .to_owned(),
+                    )
+                    .map_err(|e| DbErr::Migration(e.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .add_column(integer_null(TransactionColumn::AccountId))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Transaction::Table)
+                    .value(
+                        TransactionColumn::AccountId,
+                        Expr::col(TransactionColumn::WorkspaceId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Accounts with transactions cannot be deleted, so their balance
+        // never silently disappears from the workspace
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .modify_column(integer(TransactionColumn::AccountId).not_null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_transactions_account_id")
+                            .from_tbl(Transaction::Table)
+                            .from_col(TransactionColumn::AccountId)
+                            .to_tbl(Account::Table)
+                            .to_col(Account::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_foreign_key(Alias::new("fk_transactions_account_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_column(TransactionColumn::AccountId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Account::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Account {
+    Table,
+    Id,
+    WorkspaceId,
+    UserId,
+    Name,
+    Kind,
+    Currency,
+    Balance,
+    CreatedAt,
+    UpdatedAt,
+}
+
+/// Columns of the `transaction` table added after its creation.
+#[derive(DeriveIden)]
+pub enum TransactionColumn {
+    AccountId,
+    WorkspaceId,
+}
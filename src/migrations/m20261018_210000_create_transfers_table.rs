@@ -0,0 +1,109 @@
+use super::m20220101_000001_create_users_table::User;
+use super::m20250323_101235_create_transactions_table::Transaction;
+use super::m20261018_180000_create_workspaces_tables::Workspace;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Transfer::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Transfer::Id))
+                    .col(integer(Transfer::WorkspaceId).not_null())
+                    .col(integer_null(Transfer::UserId))
+                    .col(
+                        timestamp(Transfer::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_transfer_workspace_id")
+                            .from(Transfer::Table, Transfer::WorkspaceId)
+                            .to(Workspace::Table, Workspace::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_transfer_user_id")
+                            .from(Transfer::Table, Transfer::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .add_column(integer_null(TransactionColumn::TransferId))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_transactions_transfer_id")
+                            .from_tbl(Transaction::Table)
+                            .from_col(TransactionColumn::TransferId)
+                            .to_tbl(Transfer::Table)
+                            .to_col(Transfer::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Transfer legs only make sense in pairs. Run `reconcile --repair`
+        // afterwards to drop their effect from the balances.
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Transaction::Table)
+                    .and_where(Expr::col(TransactionColumn::TransferId).is_not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_foreign_key(Alias::new("fk_transactions_transfer_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_column(TransactionColumn::TransferId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Transfer::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Transfer {
+    Table,
+    Id,
+    WorkspaceId,
+    UserId,
+    CreatedAt,
+}
+
+/// Link from each transfer leg to its transfer.
+#[derive(DeriveIden)]
+pub enum TransactionColumn {
+    TransferId,
+}
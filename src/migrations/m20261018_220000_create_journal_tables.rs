@@ -0,0 +1,269 @@
+use super::m20220101_000001_create_users_table::User;
+use super::m20250323_095154_create_categories_table::Category;
+use super::m20250323_101235_create_transactions_table::Transaction;
+use super::m20261018_180000_create_workspaces_tables::Workspace;
+use super::m20261018_200000_create_accounts_table::Account;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JournalEntry::Table)
+                    .if_not_exists()
+                    .col(pk_auto(JournalEntry::Id))
+                    .col(integer(JournalEntry::WorkspaceId).not_null())
+                    .col(integer_null(JournalEntry::UserId))
+                    .col(
+                        timestamp(JournalEntry::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_journal_entry_workspace_id")
+                            .from(JournalEntry::Table, JournalEntry::WorkspaceId)
+                            .to(Workspace::Table, Workspace::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_journal_entry_user_id")
+                            .from(JournalEntry::Table, JournalEntry::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Each posting hits either an account or a category. Amounts are
+        // positive for debits and negative for credits.
+        manager
+            .create_table(
+                Table::create()
+                    .table(Posting::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Posting::Id))
+                    .col(integer(Posting::JournalEntryId).not_null())
+                    .col(integer_null(Posting::AccountId))
+                    .col(integer_null(Posting::CategoryId))
+                    .col(big_integer(Posting::Amount).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_posting_journal_entry_id")
+                            .from(Posting::Table, Posting::JournalEntryId)
+                            .to(JournalEntry::Table, JournalEntry::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_posting_account_id")
+                            .from(Posting::Table, Posting::AccountId)
+                            .to(Account::Table, Account::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_posting_category_id")
+                            .from(Posting::Table, Posting::CategoryId)
+                            .to(Category::Table, Category::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every existing transaction becomes an entry of its own, reusing the
+        // transaction id, with one posting on its account and an opposite one
+        // on its category
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(JournalEntry::Table)
+                    .columns([
+                        JournalEntry::Id,
+                        JournalEntry::WorkspaceId,
+                        JournalEntry::UserId,
+                        JournalEntry::CreatedAt,
+                    ])
+                    .select_from(
+                        Query::select()
+                            .column(TransactionColumn::Id)
+                            .column(TransactionColumn::WorkspaceId)
+                            .column(TransactionColumn::UserId)
+                            .column(TransactionColumn::CreatedAt)
+                            .from(Transaction::Table)
+                            .to_owned(),
+                    )
+                    .map_err(|e| DbErr::Migration(e.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+        let debit = || Expr::col(TransactionColumn::Type).eq("DEBIT");
+        let amount = || Expr::col(TransactionColumn::Amount);
+        for (column, side, posted) in [
+            (
+                Posting::AccountId,
+                TransactionColumn::AccountId,
+                Expr::case(debit(), amount().mul(-1)).finally(amount()),
+            ),
+            (
+                Posting::CategoryId,
+                TransactionColumn::CategoryId,
+                Expr::case(debit(), amount()).finally(amount().mul(-1)),
+            ),
+        ] {
+            manager
+                .exec_stmt(
+                    Query::insert()
+                        .into_table(Posting::Table)
+                        .columns([Posting::JournalEntryId, column, Posting::Amount])
+                        .select_from(
+                            Query::select()
+                                .column(TransactionColumn::Id)
+                                .column(side)
+                                .expr(posted)
+                                .from(Transaction::Table)
+                                .to_owned(),
+                        )
+                        .map_err(|e| DbErr::Migration(e.to_string()))?
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .add_column(integer_null(TransactionColumn::JournalEntryId))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Transaction::Table)
+                    .value(
+                        TransactionColumn::JournalEntryId,
+                        Expr::col(TransactionColumn::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Deleting a workspace cascades to its accounts and transactions in
+        // one statement, which a RESTRICT key on the account could block. The
+        // API refuses to delete accounts that still have transactions instead.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_foreign_key(Alias::new("fk_transactions_account_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .modify_column(integer(TransactionColumn::JournalEntryId).not_null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_transactions_journal_entry_id")
+                            .from_tbl(Transaction::Table)
+                            .from_col(TransactionColumn::JournalEntryId)
+                            .to_tbl(JournalEntry::Table)
+                            .to_col(JournalEntry::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_transactions_account_id")
+                            .from_tbl(Transaction::Table)
+                            .from_col(TransactionColumn::AccountId)
+                            .to_tbl(Account::Table)
+                            .to_col(Account::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_foreign_key(Alias::new("fk_transactions_journal_entry_id"))
+                    .drop_foreign_key(Alias::new("fk_transactions_account_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_column(TransactionColumn::JournalEntryId)
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_transactions_account_id")
+                            .from_tbl(Transaction::Table)
+                            .from_col(TransactionColumn::AccountId)
+                            .to_tbl(Account::Table)
+                            .to_col(Account::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Posting::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(JournalEntry::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum JournalEntry {
+    Table,
+    Id,
+    WorkspaceId,
+    UserId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum Posting {
+    Table,
+    Id,
+    JournalEntryId,
+    AccountId,
+    CategoryId,
+    Amount,
+}
+
+/// Columns of the `transaction` table read by the backfill, plus its new
+/// link to the journal.
+#[derive(DeriveIden)]
+pub enum TransactionColumn {
+    Id,
+    WorkspaceId,
+    UserId,
+    AccountId,
+    CategoryId,
+    Type,
+    Amount,
+    CreatedAt,
+    JournalEntryId,
+}
@@ -0,0 +1,235 @@
+use super::m20250323_095154_create_categories_table::Category;
+use super::m20250323_101235_create_transactions_table::Transaction;
+use super::m20261018_180000_create_workspaces_tables::Workspace;
+use super::m20261018_200000_create_accounts_table::Account;
+use super::m20261018_220000_create_journal_tables::Posting;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Existing balances were all entered as dollars
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workspace::Table)
+                    .add_column(
+                        char_len(WorkspaceColumn::BaseCurrency, 3)
+                            .not_null()
+                            .default("USD"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Category::Table)
+                    .add_column(
+                        char_len(CategoryColumn::Currency, 3)
+                            .not_null()
+                            .default("USD"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ExchangeRate::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ExchangeRate::Id))
+                    .col(integer(ExchangeRate::WorkspaceId).not_null())
+                    .col(char_len(ExchangeRate::FromCurrency, 3).not_null())
+                    .col(char_len(ExchangeRate::ToCurrency, 3).not_null())
+                    .col(decimal_len(ExchangeRate::Rate, 20, 10).not_null())
+                    .col(date(ExchangeRate::EffectiveOn).not_null())
+                    .col(string_len(ExchangeRate::Source, 16).not_null())
+                    .col(
+                        timestamp(ExchangeRate::CreatedAt)
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_exchange_rate_workspace_id")
+                            .from(ExchangeRate::Table, ExchangeRate::WorkspaceId)
+                            .to(Workspace::Table, Workspace::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_exchange_rate_pair_date")
+                            .col(ExchangeRate::WorkspaceId)
+                            .col(ExchangeRate::FromCurrency)
+                            .col(ExchangeRate::ToCurrency)
+                            .col(ExchangeRate::EffectiveOn)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Transactions take the currency of their account. No rates existed
+        // before, so earlier transactions are recorded at 1:1.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .add_column(char_len_null(TransactionColumn::Currency, 3))
+                    .add_column(
+                        decimal_len(TransactionColumn::ExchangeRate, 20, 10)
+                            .not_null()
+                            .default(1),
+                    )
+                    .add_column(big_integer_null(TransactionColumn::BaseAmount))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Transaction::Table)
+                    .value(
+                        TransactionColumn::Currency,
+                        SimpleExpr::SubQuery(
+                            None,
+                            Box::new(SubQueryStatement::SelectStatement(
+                                Query::select()
+                                    .column((Account::Table, Account::Currency))
+                                    .from(Account::Table)
+                                    .and_where(
+                                        Expr::col((Account::Table, Account::Id)).equals((
+                                            Transaction::Table,
+                                            TransactionColumn::AccountId,
+                                        )),
+                                    )
+                                    .to_owned(),
+                            )),
+                        ),
+                    )
+                    .value(
+                        TransactionColumn::BaseAmount,
+                        Expr::col(TransactionColumn::Amount),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .modify_column(char_len(TransactionColumn::Currency, 3).not_null())
+                    .modify_column(big_integer(TransactionColumn::BaseAmount).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Posting::Table)
+                    .add_column(big_integer_null(PostingColumn::BaseAmount))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Posting::Table)
+                    .value(PostingColumn::BaseAmount, Expr::col(Posting::Amount))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Posting::Table)
+                    .modify_column(big_integer(PostingColumn::BaseAmount).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Posting::Table)
+                    .drop_column(PostingColumn::BaseAmount)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_column(TransactionColumn::Currency)
+                    .drop_column(TransactionColumn::ExchangeRate)
+                    .drop_column(TransactionColumn::BaseAmount)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ExchangeRate::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Category::Table)
+                    .drop_column(CategoryColumn::Currency)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workspace::Table)
+                    .drop_column(WorkspaceColumn::BaseCurrency)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ExchangeRate {
+    Table,
+    Id,
+    WorkspaceId,
+    FromCurrency,
+    ToCurrency,
+    Rate,
+    EffectiveOn,
+    Source,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum WorkspaceColumn {
+    BaseCurrency,
+}
+
+#[derive(DeriveIden)]
+pub enum CategoryColumn {
+    Currency,
+}
+
+/// Columns of the `transaction` table read or added here.
+#[derive(DeriveIden)]
+pub enum TransactionColumn {
+    AccountId,
+    Amount,
+    Currency,
+    ExchangeRate,
+    BaseAmount,
+}
+
+#[derive(DeriveIden)]
+pub enum PostingColumn {
+    BaseAmount,
+}
@@ -0,0 +1,78 @@
+use super::m20250323_101235_create_transactions_table::Transaction;
+use sea_orm_migration::{prelude::*, schema::*};
+
+/// Values of `TransactionKind`, enforced by a CHECK constraint.
+const TRANSACTION_TYPES: &str = "'DEBIT', 'CREDIT', 'REFUND', 'ADJUSTMENT', 'TRANSFER'";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Rows with a type the balances did not know were posted as credits
+        // when the journal was backfilled, so they become credits here too
+        let db = manager.get_connection();
+        let coerced = db
+            .execute(
+                db.get_database_backend().build(
+                    Query::update()
+                        .table(Transaction::Table)
+                        .value(Transaction::Type, "CREDIT")
+                        .and_where(
+                            Expr::col(Transaction::Type).is_not_in(["DEBIT", "CREDIT", "TRANSFER"]),
+                        ),
+                ),
+            )
+            .await?
+            .rows_affected();
+        if coerced > 0 {
+            println!(
+                "⚠️  Changed {} transaction(s) with an unknown type to CREDIT, matching their journal postings",
+                coerced
+            );
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .modify_column(string_len(Transaction::Type, 16).not_null())
+                    .to_owned(),
+            )
+            .await?;
+        db.execute_unprepared(&format!(
+            "ALTER TABLE `transaction` ADD CONSTRAINT `chk_transaction_type` CHECK (`type` IN ({}))",
+            TRANSACTION_TYPES
+        ))
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE `transaction` DROP CHECK `chk_transaction_type`")
+            .await?;
+
+        // Refunds and adjustments carry the sign of their balance effect
+        // like credits do
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Transaction::Table)
+                    .value(Transaction::Type, "CREDIT")
+                    .and_where(Expr::col(Transaction::Type).is_in(["REFUND", "ADJUSTMENT"]))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .modify_column(string(Transaction::Type).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
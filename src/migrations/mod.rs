@@ -3,6 +3,23 @@ pub use sea_orm_migration::prelude::*;
 mod m20220101_000001_create_users_table;
 mod m20250323_095154_create_categories_table;
 mod m20250323_101235_create_transactions_table;
+mod m20261018_090000_create_sessions_table;
+mod m20261018_100000_add_role_to_users_table;
+mod m20261018_110000_create_one_time_tokens_table;
+mod m20261018_120000_add_verified_at_to_users_table;
+mod m20261018_130000_add_totp_to_users_table;
+mod m20261018_140000_create_login_attempts_table;
+mod m20261018_150000_create_personal_access_tokens_table;
+mod m20261018_160000_add_client_info_to_sessions_table;
+mod m20261018_170000_create_oidc_tables;
+mod m20261018_180000_create_workspaces_tables;
+mod m20261018_190000_create_access_grants_tables;
+mod m20261018_200000_create_accounts_table;
+mod m20261018_210000_create_transfers_table;
+mod m20261018_220000_create_journal_tables;
+mod m20261018_230000_add_currencies;
+mod m20261018_233000_restrict_transaction_types;
+mod m20261018_234000_add_date_to_transactions;
 
 pub struct Migrator;
 
@@ -13,6 +30,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20220101_000001_create_users_table::Migration),
             Box::new(m20250323_095154_create_categories_table::Migration),
             Box::new(m20250323_101235_create_transactions_table::Migration),
+            Box::new(m20261018_090000_create_sessions_table::Migration),
+            Box::new(m20261018_100000_add_role_to_users_table::Migration),
+            Box::new(m20261018_110000_create_one_time_tokens_table::Migration),
+            Box::new(m20261018_120000_add_verified_at_to_users_table::Migration),
+            Box::new(m20261018_130000_add_totp_to_users_table::Migration),
+            Box::new(m20261018_140000_create_login_attempts_table::Migration),
+            Box::new(m20261018_150000_create_personal_access_tokens_table::Migration),
+            Box::new(m20261018_160000_add_client_info_to_sessions_table::Migration),
+            Box::new(m20261018_170000_create_oidc_tables::Migration),
+            Box::new(m20261018_180000_create_workspaces_tables::Migration),
+            Box::new(m20261018_190000_create_access_grants_tables::Migration),
+            Box::new(m20261018_200000_create_accounts_table::Migration),
+            Box::new(m20261018_210000_create_transfers_table::Migration),
+            Box::new(m20261018_220000_create_journal_tables::Migration),
+            Box::new(m20261018_230000_add_currencies::Migration),
+            Box::new(m20261018_233000_restrict_transaction_types::Migration),
+            Box::new(m20261018_234000_add_date_to_transactions::Migration),
         ]
     }
 }
@@ -1,24 +1,83 @@
-use crate::middlewares::auth::verify_jwt;
+use crate::entities::sea_orm_active_enums::Role;
+use crate::middlewares::auth::{require_role, require_scope, require_session, verify_jwt};
+use crate::middlewares::workspace::resolve_workspace;
 use crate::{
-    controllers::auth::{sign_in, sign_up},
+    controllers::accounts,
+    controllers::admin,
+    controllers::auth::{
+        forgot_password, redeem_magic_link, refresh, request_magic_link, resend_verification,
+        reset_password, sign_in, sign_in_totp, sign_out, sign_up, verify_email,
+    },
+    controllers::balances::{get_reconciliation, get_summary, get_trial_balance, repair_balances},
     controllers::categories::{
         create_category, delete_category, get_all_categories, get_category_transactions,
         show_category, update_category,
     },
+    controllers::exchange_rates::{
+        create_exchange_rate, delete_exchange_rate, get_all_exchange_rates, import_exchange_rates,
+    },
+    controllers::grants::{
+        create_grant, decline_grant, get_received_grants, get_workspace_grants, revoke_grant,
+    },
+    controllers::me::{
+        change_password, delete_account, get_login_attempts, get_profile, update_profile,
+    },
+    controllers::oidc::{oidc_callback, oidc_start},
+    controllers::sessions::{get_all_sessions, revoke_all_sessions, revoke_one_session},
+    controllers::tokens::{create_token, get_all_tokens, revoke_token},
     controllers::transactions::{
         create_transaction, delete_transaction, get_all_transactions, show_transaction,
         update_transaction,
     },
+    controllers::transfers::{create_transfer, delete_transfer, get_all_transfers, show_transfer},
+    controllers::two_factor::{confirm_totp, disable_totp, enroll_totp, regenerate_recovery_codes},
+    controllers::well_known::get_jwks,
+    controllers::workspaces::{
+        create_workspace, delete_workspace, get_all_workspaces, get_invitations, get_members,
+        invite_member, join_workspace, remove_member, revoke_invitation, show_workspace,
+        update_member, update_workspace,
+    },
 };
 use actix_web::middleware::from_fn;
 use actix_web::web::{ServiceConfig, scope};
 
 pub fn configure_routes(cfg: &mut ServiceConfig) {
-    cfg.service(scope("/auth").service(sign_in).service(sign_up))
+    cfg.service(get_jwks)
+        .service(
+            scope("/auth")
+                .service(sign_in)
+                .service(sign_in_totp)
+                .service(sign_up)
+                .service(refresh)
+                .service(sign_out)
+                .service(forgot_password)
+                .service(reset_password)
+                .service(verify_email)
+                .service(resend_verification)
+                .service(request_magic_link)
+                .service(redeem_magic_link)
+                .service(oidc_start)
+                .service(oidc_callback),
+        )
+        .service(
+            scope("/admin")
+                .wrap(from_fn(require_role(Role::Admin)))
+                .wrap(from_fn(verify_jwt))
+                .service(admin::get_all_users)
+                .service(admin::disable_user)
+                .service(admin::enable_user)
+                .service(admin::unlock_user)
+                .service(admin::get_user_login_attempts)
+                .service(admin::get_stats)
+                .service(admin::get_reconciliation)
+                .service(admin::repair_balances),
+        )
         .service(
             scope("/api")
                 .service(
                     scope("/categories")
+                        .wrap(from_fn(resolve_workspace))
+                        .wrap(from_fn(require_scope("categories")))
                         .wrap(from_fn(verify_jwt))
                         .service(get_all_categories)
                         .service(create_category)
@@ -27,14 +86,116 @@ pub fn configure_routes(cfg: &mut ServiceConfig) {
                         .service(delete_category)
                         .service(get_category_transactions),
                 )
+                .service(
+                    scope("/accounts")
+                        .wrap(from_fn(resolve_workspace))
+                        .wrap(from_fn(require_scope("accounts")))
+                        .wrap(from_fn(verify_jwt))
+                        .service(accounts::get_all_accounts)
+                        .service(accounts::create_account)
+                        .service(accounts::show_account)
+                        .service(accounts::update_account)
+                        .service(accounts::delete_account)
+                        .service(accounts::get_account_transactions),
+                )
                 .service(
                     scope("/transactions")
+                        .wrap(from_fn(resolve_workspace))
+                        .wrap(from_fn(require_scope("transactions")))
                         .wrap(from_fn(verify_jwt))
                         .service(get_all_transactions)
                         .service(create_transaction)
                         .service(show_transaction)
                         .service(update_transaction)
                         .service(delete_transaction),
+                )
+                .service(
+                    scope("/transfers")
+                        .wrap(from_fn(resolve_workspace))
+                        .wrap(from_fn(require_scope("transactions")))
+                        .wrap(from_fn(verify_jwt))
+                        .service(get_all_transfers)
+                        .service(create_transfer)
+                        .service(show_transfer)
+                        .service(delete_transfer),
+                )
+                .service(
+                    scope("/exchange-rates")
+                        .wrap(from_fn(resolve_workspace))
+                        .wrap(from_fn(require_scope("exchange_rates")))
+                        .wrap(from_fn(verify_jwt))
+                        .service(get_all_exchange_rates)
+                        .service(create_exchange_rate)
+                        .service(import_exchange_rates)
+                        .service(delete_exchange_rate),
+                )
+                .service(
+                    scope("/me")
+                        .wrap(from_fn(require_session))
+                        .wrap(from_fn(verify_jwt))
+                        .service(get_profile)
+                        .service(update_profile)
+                        .service(change_password)
+                        .service(delete_account)
+                        .service(get_login_attempts)
+                        .service(
+                            scope("/sessions")
+                                .service(get_all_sessions)
+                                .service(revoke_one_session)
+                                .service(revoke_all_sessions),
+                        )
+                        .service(
+                            scope("/totp")
+                                .service(enroll_totp)
+                                .service(confirm_totp)
+                                .service(regenerate_recovery_codes)
+                                .service(disable_totp),
+                        ),
+                )
+                .service(
+                    scope("/balances")
+                        .wrap(from_fn(resolve_workspace))
+                        .wrap(from_fn(require_scope("balances")))
+                        .wrap(from_fn(verify_jwt))
+                        .service(get_summary)
+                        .service(get_trial_balance)
+                        .service(get_reconciliation)
+                        .service(repair_balances),
+                )
+                .service(
+                    scope("/tokens")
+                        .wrap(from_fn(require_session))
+                        .wrap(from_fn(verify_jwt))
+                        .service(get_all_tokens)
+                        .service(create_token)
+                        .service(revoke_token),
+                )
+                .service(
+                    scope("/workspaces")
+                        .wrap(from_fn(require_session))
+                        .wrap(from_fn(verify_jwt))
+                        .service(join_workspace)
+                        .service(get_all_workspaces)
+                        .service(create_workspace)
+                        .service(show_workspace)
+                        .service(update_workspace)
+                        .service(delete_workspace)
+                        .service(get_members)
+                        .service(update_member)
+                        .service(remove_member)
+                        .service(get_invitations)
+                        .service(invite_member)
+                        .service(revoke_invitation)
+                        .service(get_workspace_grants)
+                        .service(create_grant)
+                        .service(revoke_grant),
+                )
+                .service(
+                    scope("/grants")
+                        .wrap(from_fn(require_session))
+                        .wrap(from_fn(verify_jwt))
+                        .service(get_received_grants)
+                        .service(decline_grant),
                 ),
         );
 }
@@ -0,0 +1,73 @@
+use chrono::Utc;
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect};
+
+use crate::entities::{
+    access_grant::{self, Entity as AccessGrant},
+    access_grant_category::{self, Entity as AccessGrantCategory},
+    sea_orm_active_enums::{GrantAccess, WorkspaceRole},
+    workspace_member::{self, Entity as WorkspaceMember},
+};
+use crate::utils::workspaces::WorkspaceAccess;
+
+/// Request header with which a grantee acts through one of their grants.
+pub const ACCESS_GRANT_HEADER: &str = "X-Access-Grant";
+
+/// Resolves grant `grant_id` for `grantee_id` into the access it gives, or
+/// `None` when the grant does not exist, belongs to someone else, has been
+/// revoked or expired, or its grantor is no longer an owner of the workspace.
+pub async fn resolve_access_grant<C: ConnectionTrait>(
+    db: &C,
+    grant_id: i32,
+    grantee_id: i32,
+) -> Result<Option<WorkspaceAccess>, DbErr> {
+    let Some(grant) = AccessGrant::find_by_id(grant_id)
+        .filter(access_grant::Column::GranteeId.eq(grantee_id))
+        .filter(access_grant::Column::RevokedAt.is_null())
+        .filter(access_grant::Column::ExpiresAt.gt(Utc::now()))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    // Grants stand on the owner role needed to create them
+    let Some(grantor_id) = grant.grantor_id else {
+        return Ok(None);
+    };
+    let grantor_is_owner = WorkspaceMember::find()
+        .filter(workspace_member::Column::WorkspaceId.eq(grant.workspace_id))
+        .filter(workspace_member::Column::UserId.eq(grantor_id))
+        .filter(workspace_member::Column::Role.eq(WorkspaceRole::Owner))
+        .one(db)
+        .await?
+        .is_some();
+    if !grantor_is_owner {
+        return Ok(None);
+    }
+
+    let category_ids = if grant.all_categories {
+        None
+    } else {
+        Some(grant_category_ids(db, grant.id).await?)
+    };
+
+    Ok(Some(WorkspaceAccess {
+        workspace_id: grant.workspace_id,
+        user_id: grantee_id,
+        can_write: grant.access == GrantAccess::Write,
+        category_ids,
+    }))
+}
+
+pub async fn grant_category_ids<C: ConnectionTrait>(
+    db: &C,
+    grant_id: i32,
+) -> Result<Vec<i32>, DbErr> {
+    AccessGrantCategory::find()
+        .select_only()
+        .column(access_grant_category::Column::CategoryId)
+        .filter(access_grant_category::Column::AccessGrantId.eq(grant_id))
+        .into_tuple()
+        .all(db)
+        .await
+}
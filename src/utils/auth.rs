@@ -1,5 +1,49 @@
 use actix_web::{HttpMessage, HttpRequest};
 
+use crate::controllers::auth::Claims;
+use crate::utils::responses::unauthorized;
+use crate::utils::workspaces::WorkspaceAccess;
+
 pub fn get_user_by_id(req: &HttpRequest) -> i32 {
     req.extensions().get::<i32>().copied().unwrap()
 }
+
+/// Access to the workspace the request acts in. Only available behind the
+/// `resolve_workspace` middleware.
+pub fn get_workspace_access(req: &HttpRequest) -> WorkspaceAccess {
+    req.extensions().get::<WorkspaceAccess>().cloned().unwrap()
+}
+
+/// Claims of the session token that authenticated the request. Fails for
+/// requests made with a personal access token.
+pub fn get_claims(req: &HttpRequest) -> Result<Claims, actix_web::Error> {
+    req.extensions()
+        .get::<Claims>()
+        .cloned()
+        .ok_or_else(|| unauthorized("Missing session"))
+}
+
+/// IP address of the connected peer. Forwarding headers are ignored because
+/// they are client-controlled.
+pub fn client_ip(req: &HttpRequest) -> String {
+    req.peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Where a request came from, recorded on the sessions it opens.
+pub struct ClientInfo {
+    pub ip: String,
+    pub user_agent: Option<String>,
+}
+
+pub fn client_info(req: &HttpRequest) -> ClientInfo {
+    ClientInfo {
+        ip: client_ip(req),
+        user_agent: req
+            .headers()
+            .get("User-Agent")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(255).collect()),
+    }
+}
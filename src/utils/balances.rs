@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, JoinType, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait, Set, TransactionTrait,
+    sea_query::{Alias, Expr, Func},
+};
+use serde::Serialize;
+
+use crate::entities::{
+    account::{self, Entity as Account},
+    category::{self, Entity as Category},
+    journal_entry,
+    posting::{self, Entity as Posting},
+    sea_orm_active_enums::TransactionKind,
+    transaction::{self, Entity as Transaction},
+    workspace::{self, Entity as Workspace},
+};
+use crate::utils::money::Money;
+use crate::utils::workspaces::base_currency;
+
+/// A stored balance that differs from the journal, in minor units.
+#[derive(Serialize, Debug)]
+pub struct Discrepancy {
+    pub entity: &'static str,
+    pub id: i32,
+    pub stored: i64,
+    pub expected: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReconciliationReport {
+    pub workspaces_checked: usize,
+    pub accounts_checked: usize,
+    pub categories_checked: usize,
+    pub discrepancies: Vec<Discrepancy>,
+    pub repaired: bool,
+}
+
+/// Money that came in and went out of a workspace, in its base currency.
+#[derive(Serialize, Debug)]
+pub struct CashFlow {
+    pub income: Money,
+    pub expense: Money,
+    pub net: Money,
+}
+
+/// Debit and credit totals of one account or category in the journal, in its
+/// own currency.
+#[derive(Serialize, Debug)]
+pub struct TrialBalanceLine {
+    pub entity: &'static str,
+    pub id: i32,
+    pub name: String,
+    pub debit: Money,
+    pub credit: Money,
+}
+
+/// Every account and category of a workspace with its posting totals. The
+/// totals are converted to the base currency at the rates recorded on each
+/// transaction. The books are `balanced` when debits equal credits and every
+/// journal entry sums to zero on its own.
+#[derive(Serialize, Debug)]
+pub struct TrialBalance {
+    pub lines: Vec<TrialBalanceLine>,
+    pub total_debit: Money,
+    pub total_credit: Money,
+    pub unbalanced_entries: Vec<i32>,
+    pub balanced: bool,
+}
+
+/// Total of the cash flow report a transaction counts toward.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    Income,
+    Expense,
+}
+
+impl TransactionKind {
+    /// Whether the amount carries the direction in its sign. Other kinds take
+    /// positive amounts and get the direction from the kind.
+    pub fn is_signed(self) -> bool {
+        matches!(self, Self::Adjustment | Self::Transfer)
+    }
+
+    /// Checks the amount entered for a transaction of this kind.
+    pub fn check_amount(self, amount: i64) -> Result<(), &'static str> {
+        if self.is_signed() {
+            if amount == 0 {
+                return Err("amount must not be zero");
+            }
+        } else if amount <= 0 {
+            return Err("amount must be positive");
+        }
+        Ok(())
+    }
+
+    /// Signed effect of a transaction on the workspace, account and category
+    /// balances: debits take money out, credits and refunds put it back.
+    /// Returns `None` on overflow.
+    pub fn balance_delta(self, amount: i64) -> Option<i64> {
+        match self {
+            Self::Debit => amount.checked_neg(),
+            Self::Credit | Self::Refund | Self::Adjustment | Self::Transfer => Some(amount),
+        }
+    }
+
+    /// Total of the cash flow report the kind counts toward, and the sign it
+    /// counts with. Refunds reduce expenses; adjustments and transfers count
+    /// as neither.
+    pub fn flow(self) -> Option<(Flow, i64)> {
+        match self {
+            Self::Credit => Some((Flow::Income, 1)),
+            Self::Debit => Some((Flow::Expense, 1)),
+            Self::Refund => Some((Flow::Expense, -1)),
+            Self::Adjustment | Self::Transfer => None,
+        }
+    }
+}
+
+/// Recomputes workspace, account and category balances from the journal
+/// postings and compares them with the stored counters. When
+/// `repair` is set, the stored counters are overwritten with the recomputed
+/// values.
+///
+/// `workspace_id` limits the check to a single workspace; `None` checks
+/// everyone.
+pub async fn reconcile(
+    db: &DatabaseConnection,
+    workspace_id: Option<i32>,
+    repair: bool,
+) -> Result<ReconciliationReport, DbErr> {
+    let txn = db.begin().await?;
+
+    // Lock in the same order as the transaction handlers (workspaces, then
+    // accounts, then categories) so a repair never races a concurrent ledger
+    // write.
+    let mut workspaces = Workspace::find();
+    let mut accounts = Account::find();
+    let mut categories = Category::find();
+    if let Some(workspace_id) = workspace_id {
+        workspaces = workspaces.filter(workspace::Column::Id.eq(workspace_id));
+        accounts = accounts.filter(account::Column::WorkspaceId.eq(workspace_id));
+        categories = categories.filter(category::Column::WorkspaceId.eq(workspace_id));
+    }
+    if repair {
+        workspaces = workspaces.lock_exclusive();
+        accounts = accounts.lock_exclusive();
+        categories = categories.lock_exclusive();
+    }
+    let workspaces = workspaces.all(&txn).await?;
+    let accounts = accounts.all(&txn).await?;
+    let categories = categories.all(&txn).await?;
+
+    // Category postings carry the opposite sign of the account postings they
+    // balance, and a workspace holds the sum of its accounts in its base
+    // currency
+    let workspace_totals = posting_totals(
+        &txn,
+        journal_entry::Column::WorkspaceId,
+        posting::Column::AccountId,
+        posting::Column::BaseAmount,
+        workspace_id,
+    )
+    .await?;
+    let account_totals = posting_totals(
+        &txn,
+        posting::Column::AccountId,
+        posting::Column::AccountId,
+        posting::Column::Amount,
+        workspace_id,
+    )
+    .await?;
+    let category_totals: HashMap<i32, i64> = posting_totals(
+        &txn,
+        posting::Column::CategoryId,
+        posting::Column::CategoryId,
+        posting::Column::Amount,
+        workspace_id,
+    )
+    .await?
+    .into_iter()
+    .map(|(id, total)| (id, -total))
+    .collect();
+
+    let mut discrepancies = Vec::new();
+
+    for workspace in &workspaces {
+        let expected = workspace_totals.get(&workspace.id).copied().unwrap_or(0);
+        if workspace.balance != expected {
+            discrepancies.push(Discrepancy {
+                entity: "workspace",
+                id: workspace.id,
+                stored: workspace.balance,
+                expected,
+            });
+            if repair {
+                Workspace::update(workspace::ActiveModel {
+                    id: Set(workspace.id),
+                    balance: Set(expected),
+                    ..Default::default()
+                })
+                .exec(&txn)
+                .await?;
+            }
+        }
+    }
+
+    for account in &accounts {
+        let expected = account_totals.get(&account.id).copied().unwrap_or(0);
+        if account.balance != expected {
+            discrepancies.push(Discrepancy {
+                entity: "account",
+                id: account.id,
+                stored: account.balance,
+                expected,
+            });
+            if repair {
+                Account::update(account::ActiveModel {
+                    id: Set(account.id),
+                    balance: Set(expected),
+                    ..Default::default()
+                })
+                .exec(&txn)
+                .await?;
+            }
+        }
+    }
+
+    for category in &categories {
+        let expected = category_totals.get(&category.id).copied().unwrap_or(0);
+        if category.balance != expected {
+            discrepancies.push(Discrepancy {
+                entity: "category",
+                id: category.id,
+                stored: category.balance,
+                expected,
+            });
+            if repair {
+                Category::update(category::ActiveModel {
+                    id: Set(category.id),
+                    balance: Set(expected),
+                    ..Default::default()
+                })
+                .exec(&txn)
+                .await?;
+            }
+        }
+    }
+
+    txn.commit().await?;
+
+    Ok(ReconciliationReport {
+        workspaces_checked: workspaces.len(),
+        accounts_checked: accounts.len(),
+        categories_checked: categories.len(),
+        discrepancies,
+        repaired: repair,
+    })
+}
+
+/// Totals income and expense as set out by `TransactionKind::flow`,
+/// converted to the base currency at the rate recorded on each transaction.
+/// `category_ids` limits the totals to some categories.
+pub async fn cash_flow(
+    db: &DatabaseConnection,
+    workspace_id: i32,
+    category_ids: Option<Vec<i32>>,
+) -> Result<CashFlow, DbErr> {
+    let currency = base_currency(db, workspace_id).await?;
+    let mut query = Transaction::find()
+        .select_only()
+        .column(transaction::Column::Type)
+        .expr(Func::cast_as(
+            Func::sum(Expr::col(transaction::Column::BaseAmount)),
+            Alias::new("SIGNED"),
+        ))
+        .filter(transaction::Column::WorkspaceId.eq(workspace_id))
+        .group_by(transaction::Column::Type);
+    if let Some(category_ids) = category_ids {
+        query = query.filter(transaction::Column::CategoryId.is_in(category_ids));
+    }
+
+    let rows: Vec<(TransactionKind, i64)> = query.into_tuple().all(db).await?;
+
+    let mut income: i64 = 0;
+    let mut expense: i64 = 0;
+    for (kind, amount) in rows {
+        let Some((flow, sign)) = kind.flow() else {
+            continue;
+        };
+        let total = match flow {
+            Flow::Income => &mut income,
+            Flow::Expense => &mut expense,
+        };
+        let amount = amount.checked_mul(sign).ok_or_else(overflow)?;
+        *total = total.checked_add(amount).ok_or_else(overflow)?;
+    }
+    let net = income.checked_sub(expense).ok_or_else(overflow)?;
+
+    Ok(CashFlow {
+        income: Money::new(income, currency.clone()),
+        expense: Money::new(expense, currency.clone()),
+        net: Money::new(net, currency),
+    })
+}
+
+/// Lists the debit and credit totals of every account and category of a
+/// workspace, taken from the journal postings.
+pub async fn trial_balance(
+    db: &DatabaseConnection,
+    workspace_id: i32,
+) -> Result<TrialBalance, DbErr> {
+    let base_currency = base_currency(db, workspace_id).await?;
+    let accounts = Account::find()
+        .filter(account::Column::WorkspaceId.eq(workspace_id))
+        .order_by_asc(account::Column::Id)
+        .all(db)
+        .await?;
+    let categories = Category::find()
+        .filter(category::Column::WorkspaceId.eq(workspace_id))
+        .order_by_asc(category::Column::Id)
+        .all(db)
+        .await?;
+    let account_sides = posting_sides(
+        db,
+        posting::Column::AccountId,
+        posting::Column::Amount,
+        workspace_id,
+    )
+    .await?;
+    let category_sides = posting_sides(
+        db,
+        posting::Column::CategoryId,
+        posting::Column::Amount,
+        workspace_id,
+    )
+    .await?;
+    let (total_debit, total_credit) = posting_sides(
+        db,
+        journal_entry::Column::WorkspaceId,
+        posting::Column::BaseAmount,
+        workspace_id,
+    )
+    .await?
+    .get(&workspace_id)
+    .copied()
+    .unwrap_or((0, 0));
+
+    let mut lines = Vec::with_capacity(accounts.len() + categories.len());
+    for account in accounts {
+        let (debit, credit) = account_sides.get(&account.id).copied().unwrap_or((0, 0));
+        lines.push(TrialBalanceLine {
+            entity: "account",
+            id: account.id,
+            name: account.name,
+            debit: Money::new(debit, account.currency.clone()),
+            credit: Money::new(credit, account.currency),
+        });
+    }
+    for category in categories {
+        let (debit, credit) = category_sides.get(&category.id).copied().unwrap_or((0, 0));
+        lines.push(TrialBalanceLine {
+            entity: "category",
+            id: category.id,
+            name: category.name,
+            debit: Money::new(debit, category.currency.clone()),
+            credit: Money::new(credit, category.currency),
+        });
+    }
+
+    let unbalanced_entries: Vec<i32> = Posting::find()
+        .select_only()
+        .column(posting::Column::JournalEntryId)
+        .join(JoinType::InnerJoin, posting::Relation::JournalEntry.def())
+        .filter(journal_entry::Column::WorkspaceId.eq(workspace_id))
+        .group_by(posting::Column::JournalEntryId)
+        .having(Expr::expr(Func::sum(Expr::col(posting::Column::Amount))).ne(0))
+        .order_by_asc(posting::Column::JournalEntryId)
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let balanced = total_debit == total_credit && unbalanced_entries.is_empty();
+    Ok(TrialBalance {
+        lines,
+        total_debit: Money::new(total_debit, base_currency.clone()),
+        total_credit: Money::new(total_credit, base_currency),
+        balanced,
+        unbalanced_entries,
+    })
+}
+
+fn overflow() -> DbErr {
+    DbErr::Custom("Amount overflow".to_string())
+}
+
+/// Sums the `amount` (native or base) of the postings on `side` (account or
+/// category id) per `group_by` column.
+async fn posting_totals<C: ConnectionTrait, G: ColumnTrait>(
+    db: &C,
+    group_by: G,
+    side: posting::Column,
+    amount: posting::Column,
+    workspace_id: Option<i32>,
+) -> Result<HashMap<i32, i64>, DbErr> {
+    let mut query = Posting::find()
+        .select_only()
+        .column(group_by)
+        .expr(Func::cast_as(
+            Func::sum(Expr::col(amount)),
+            Alias::new("SIGNED"),
+        ))
+        .join(JoinType::InnerJoin, posting::Relation::JournalEntry.def())
+        .filter(side.is_not_null())
+        .group_by(group_by);
+    if let Some(workspace_id) = workspace_id {
+        query = query.filter(journal_entry::Column::WorkspaceId.eq(workspace_id));
+    }
+
+    let rows: Vec<(i32, i64)> = query.into_tuple().all(db).await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Sums the debit (positive) and credit (negative) `amount` of a workspace's
+/// postings separately, per `group_by` column. Credits are returned as
+/// positive numbers.
+async fn posting_sides<G: ColumnTrait>(
+    db: &DatabaseConnection,
+    group_by: G,
+    amount: posting::Column,
+    workspace_id: i32,
+) -> Result<HashMap<i32, (i64, i64)>, DbErr> {
+    let amount = || Expr::col(amount);
+    let rows: Vec<(i32, i64, i64)> = Posting::find()
+        .select_only()
+        .column(group_by)
+        .expr(Func::cast_as(
+            Func::sum(Expr::case(amount().gt(0), amount()).finally(0)),
+            Alias::new("SIGNED"),
+        ))
+        .expr(Func::cast_as(
+            Func::sum(Expr::case(amount().lt(0), amount().mul(-1)).finally(0)),
+            Alias::new("SIGNED"),
+        ))
+        .join(JoinType::InnerJoin, posting::Relation::JournalEntry.def())
+        .filter(journal_entry::Column::WorkspaceId.eq(workspace_id))
+        .filter(group_by.is_not_null())
+        .group_by(group_by)
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, debit, credit)| (id, (debit, credit)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balance_delta_gives_each_kind_its_direction() {
+        assert_eq!(TransactionKind::Debit.balance_delta(500), Some(-500));
+        assert_eq!(TransactionKind::Credit.balance_delta(500), Some(500));
+        assert_eq!(TransactionKind::Refund.balance_delta(500), Some(500));
+        assert_eq!(TransactionKind::Adjustment.balance_delta(-500), Some(-500));
+        assert_eq!(TransactionKind::Transfer.balance_delta(-500), Some(-500));
+        assert_eq!(TransactionKind::Debit.balance_delta(i64::MIN), None);
+    }
+
+    #[test]
+    fn check_amount_requires_positive_amounts_for_unsigned_kinds() {
+        for kind in [
+            TransactionKind::Debit,
+            TransactionKind::Credit,
+            TransactionKind::Refund,
+        ] {
+            assert!(kind.check_amount(1).is_ok());
+            assert!(kind.check_amount(0).is_err());
+            assert!(kind.check_amount(-1).is_err());
+        }
+        for kind in [TransactionKind::Adjustment, TransactionKind::Transfer] {
+            assert!(kind.check_amount(1).is_ok());
+            assert!(kind.check_amount(-1).is_ok());
+            assert!(kind.check_amount(0).is_err());
+        }
+    }
+
+    #[test]
+    fn flow_counts_refunds_against_expenses() {
+        assert_eq!(TransactionKind::Credit.flow(), Some((Flow::Income, 1)));
+        assert_eq!(TransactionKind::Debit.flow(), Some((Flow::Expense, 1)));
+        assert_eq!(TransactionKind::Refund.flow(), Some((Flow::Expense, -1)));
+        assert_eq!(TransactionKind::Adjustment.flow(), None);
+        assert_eq!(TransactionKind::Transfer.flow(), None);
+    }
+
+    #[test]
+    fn kinds_deserialize_from_uppercase_names_only() {
+        let kind: TransactionKind = serde_json::from_str("\"REFUND\"").unwrap();
+        assert_eq!(kind, TransactionKind::Refund);
+        assert!(serde_json::from_str::<TransactionKind>("\"refund\"").is_err());
+        assert!(serde_json::from_str::<TransactionKind>("\"WITHDRAWAL\"").is_err());
+    }
+}
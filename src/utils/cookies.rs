@@ -0,0 +1,158 @@
+use std::env;
+
+use actix_web::{
+    HttpRequest, HttpResponseBuilder,
+    cookie::{Cookie, SameSite, time},
+};
+
+use crate::utils::jwt::ACCESS_TOKEN_LIFETIME;
+use crate::utils::responses::forbidden;
+use crate::utils::sessions::{IssuedTokens, REFRESH_TOKEN_LIFETIME};
+use crate::utils::tokens::{generate_token, hash_token};
+
+pub const ACCESS_COOKIE: &str = "spenser_access";
+pub const REFRESH_COOKIE: &str = "spenser_refresh";
+pub const CSRF_COOKIE: &str = "spenser_csrf";
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+/// Request header with which browser clients opt into cookie delivery.
+pub const AUTH_MODE_HEADER: &str = "X-Auth-Mode";
+/// Binds an OIDC sign-in to the browser that started it.
+pub const OIDC_STATE_COOKIE: &str = "spenser_oidc_state";
+
+/// Attributes of the authentication cookies. Only present when cookie mode is
+/// enabled with `AUTH_COOKIES=true`.
+pub struct CookiePolicy {
+    secure: bool,
+    same_site: SameSite,
+    domain: Option<String>,
+}
+
+impl CookiePolicy {
+    pub fn from_env() -> Option<Self> {
+        if env::var("AUTH_COOKIES").is_ok_and(|value| value == "true") {
+            Some(Self {
+                secure: env::var("COOKIE_SECURE").map_or(true, |value| value != "false"),
+                same_site: match env::var("COOKIE_SAME_SITE").as_deref() {
+                    Ok("lax") => SameSite::Lax,
+                    Ok("none") => SameSite::None,
+                    _ => SameSite::Strict,
+                },
+                domain: env::var("COOKIE_DOMAIN")
+                    .ok()
+                    .filter(|value| !value.is_empty()),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Stores `tokens` in HttpOnly cookies and sets a fresh CSRF cookie, whose
+    /// value is returned so it can also be sent in the response body.
+    pub fn set_auth_cookies(
+        &self,
+        response: &mut HttpResponseBuilder,
+        tokens: &IssuedTokens,
+    ) -> String {
+        let csrf_token = generate_token();
+        response
+            .cookie(self.cookie(
+                ACCESS_COOKIE,
+                tokens.access_token.clone(),
+                "/",
+                true,
+                ACCESS_TOKEN_LIFETIME,
+            ))
+            .cookie(self.cookie(
+                REFRESH_COOKIE,
+                tokens.refresh_token.clone(),
+                "/auth",
+                true,
+                REFRESH_TOKEN_LIFETIME,
+            ))
+            .cookie(self.cookie(
+                CSRF_COOKIE,
+                csrf_token.clone(),
+                "/",
+                false,
+                REFRESH_TOKEN_LIFETIME,
+            ));
+        csrf_token
+    }
+
+    pub fn clear_auth_cookies(&self, response: &mut HttpResponseBuilder) {
+        for (name, path, http_only) in [
+            (ACCESS_COOKIE, "/", true),
+            (REFRESH_COOKIE, "/auth", true),
+            (CSRF_COOKIE, "/", false),
+        ] {
+            let mut cookie = self.cookie(
+                name,
+                String::new(),
+                path,
+                http_only,
+                chrono::Duration::zero(),
+            );
+            cookie.make_removal();
+            response.cookie(cookie);
+        }
+    }
+
+    fn cookie(
+        &self,
+        name: &'static str,
+        value: String,
+        path: &'static str,
+        http_only: bool,
+        max_age: chrono::Duration,
+    ) -> Cookie<'static> {
+        let mut cookie = Cookie::build(name, value)
+            .path(path)
+            .http_only(http_only)
+            .secure(self.secure)
+            .same_site(self.same_site)
+            .max_age(time::Duration::seconds(max_age.num_seconds()))
+            .finish();
+        if let Some(domain) = &self.domain {
+            cookie.set_domain(domain.clone());
+        }
+        cookie
+    }
+}
+
+/// HttpOnly cookie carrying the `state` of an OIDC sign-in. It is set whether
+/// or not cookie mode is enabled, and is `Lax` so the browser sends it along
+/// with the provider's redirect back to the callback.
+pub fn oidc_state_cookie(state: String, max_age: chrono::Duration) -> Cookie<'static> {
+    Cookie::build(OIDC_STATE_COOKIE, state)
+        .path("/auth/oidc")
+        .http_only(true)
+        .secure(env::var("COOKIE_SECURE").map_or(true, |value| value != "false"))
+        .same_site(SameSite::Lax)
+        .max_age(time::Duration::seconds(max_age.num_seconds()))
+        .finish()
+}
+
+/// Whether the client asked for tokens in cookies rather than the body.
+pub fn wants_cookies(policy: Option<&CookiePolicy>, req: &HttpRequest) -> bool {
+    policy.is_some()
+        && req
+            .headers()
+            .get(AUTH_MODE_HEADER)
+            .is_some_and(|value| value == "cookie")
+}
+
+/// Double-submit check for cookie-authenticated requests: the CSRF header
+/// must repeat the CSRF cookie, which other sites cannot read.
+pub fn check_csrf(req: &HttpRequest) -> Result<(), actix_web::Error> {
+    let cookie = req.cookie(CSRF_COOKIE);
+    let header = req
+        .headers()
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    match (cookie, header) {
+        // Compare digests so the check does not leak timing information
+        (Some(cookie), Some(header)) if hash_token(cookie.value()) == hash_token(header) => Ok(()),
+        _ => Err(forbidden("Missing or invalid CSRF token")),
+    }
+}
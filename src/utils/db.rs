@@ -7,14 +7,14 @@ use std::io;
 pub async fn run_migrations(db: &DatabaseConnection) -> io::Result<()> {
     Migrator::up(db, None)
         .await
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("❌ Migration failed: {}", e)))?;
+        .map_err(|e| io::Error::other(format!("❌ Migration failed: {}", e)))?;
     println!("✅ Migrations applied successfully");
     Ok(())
 }
 
 pub async fn establish_connection() -> std::io::Result<DatabaseConnection> {
-    let database_url = env::var("DATABASE_URL")
-        .map_err(|_| std::io::Error::new(std::io::ErrorKind::Other, "❌ DATABASE_URL not set"))?;
+    let database_url =
+        env::var("DATABASE_URL").map_err(|_| std::io::Error::other("❌ DATABASE_URL not set"))?;
 
     let mut opt = ConnectOptions::new(database_url);
     opt.sqlx_logging(false);
@@ -26,10 +26,7 @@ pub async fn establish_connection() -> std::io::Result<DatabaseConnection> {
         }
         Err(e) => {
             eprintln!("❌ Failed to connect to database: {}", e);
-            Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "❌ Database connection failed",
-            ))
+            Err(std::io::Error::other("❌ Database connection failed"))
         }
     }
 }
@@ -0,0 +1,194 @@
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder, Set,
+    sea_query::OnConflict,
+};
+use serde_json::json;
+
+use crate::entities::{
+    exchange_rate::{self, ActiveModel as ExchangeRateActiveModel, Entity as ExchangeRate},
+    sea_orm_active_enums::RateSource,
+};
+use crate::utils::money::Money;
+use crate::utils::validation::invalid_request;
+
+/// Decimal places kept for a rate, matching the `exchange_rate.rate` column.
+const RATE_SCALE: u32 = 10;
+
+/// One unit of `from_currency` is worth `rate` units of `to_currency` from
+/// `effective_on` onwards.
+pub struct RateRow {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub rate: Decimal,
+    pub effective_on: NaiveDate,
+}
+
+/// Whether `code` looks like an ISO 4217 currency code such as `EUR`.
+pub fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.bytes().all(|byte| byte.is_ascii_uppercase())
+}
+
+/// Normalises a currency from a request to upper case and checks that it is
+/// a three-letter ISO 4217 code.
+pub fn validate_currency(currency: &str) -> Result<String, actix_web::Error> {
+    let currency = currency.trim().to_ascii_uppercase();
+    if !is_currency_code(&currency) {
+        return Err(invalid_request(
+            "currency must be a three-letter ISO 4217 code",
+        ));
+    }
+    Ok(currency)
+}
+
+/// Rounds `rate` to the stored precision, rejecting rates that are not
+/// positive or do not fit the column.
+pub fn check_rate(rate: Decimal) -> Option<Decimal> {
+    let rate = rate.round_dp(RATE_SCALE);
+    (rate > Decimal::ZERO && rate < Decimal::from(10_000_000_000i64)).then_some(rate)
+}
+
+/// Rate converting `from` into `to` on `on`, taken from the latest rate of the
+/// workspace entered on or before that day. A rate entered for the opposite
+/// direction is inverted when it is the more recent one.
+pub async fn find_rate<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: i32,
+    from: &str,
+    to: &str,
+    on: NaiveDate,
+) -> Result<Decimal, actix_web::Error> {
+    if from == to {
+        return Ok(Decimal::ONE);
+    }
+
+    let direct = latest_rate(db, workspace_id, from, to, on).await?;
+    let inverse = latest_rate(db, workspace_id, to, from, on).await?;
+    let rate = match (direct, inverse) {
+        (Some(direct), Some(inverse)) if inverse.effective_on > direct.effective_on => {
+            Decimal::ONE / inverse.rate
+        }
+        (Some(direct), _) => direct.rate,
+        (None, Some(inverse)) => Decimal::ONE / inverse.rate,
+        (None, None) => {
+            return Err(actix_web::error::ErrorBadRequest(json!({
+                "status": "error",
+                "message": format!("No exchange rate from {} to {}", from, to)
+            })));
+        }
+    };
+    Ok(rate.round_dp(RATE_SCALE))
+}
+
+/// Converts `amount` to `currency` at `rate`, rounding half away from zero so
+/// both legs of a transfer convert to the same magnitude.
+pub fn convert(amount: &Money, rate: Decimal, currency: &str) -> Result<Money, actix_web::Error> {
+    amount.convert(rate, currency).ok_or_else(|| {
+        actix_web::error::ErrorBadRequest(
+            json!({"status": "error", "message": "Amount is too large to convert"}),
+        )
+    })
+}
+
+/// Stores `rows` for a workspace, replacing any rate already entered for the
+/// same currency pair and day.
+pub async fn save_rates<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: i32,
+    rows: &[RateRow],
+    source: RateSource,
+) -> Result<(), DbErr> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let models = rows.iter().map(|row| ExchangeRateActiveModel {
+        workspace_id: Set(workspace_id),
+        from_currency: Set(row.from_currency.clone()),
+        to_currency: Set(row.to_currency.clone()),
+        rate: Set(row.rate),
+        effective_on: Set(row.effective_on),
+        source: Set(source),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    });
+    ExchangeRate::insert_many(models)
+        .on_conflict(
+            OnConflict::columns([
+                exchange_rate::Column::WorkspaceId,
+                exchange_rate::Column::FromCurrency,
+                exchange_rate::Column::ToCurrency,
+                exchange_rate::Column::EffectiveOn,
+            ])
+            .update_columns([
+                exchange_rate::Column::Rate,
+                exchange_rate::Column::Source,
+                exchange_rate::Column::CreatedAt,
+            ])
+            .to_owned(),
+        )
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Parses a rate file with one `effective_on,from_currency,to_currency,rate`
+/// line per rate, such as `2026-10-01,EUR,USD,1.0834`. A header line, blank
+/// lines and lines starting with `#` are skipped. Errors name the offending
+/// line.
+pub fn parse_rates(file: &str) -> Result<Vec<RateRow>, String> {
+    let mut rows = Vec::new();
+    for (index, line) in file.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("effective_on") {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [effective_on, from_currency, to_currency, rate] = fields[..] else {
+            return Err(format!("Line {}: expected 4 fields", index + 1));
+        };
+        let effective_on = NaiveDate::parse_from_str(effective_on, "%Y-%m-%d")
+            .map_err(|_| format!("Line {}: invalid date", index + 1))?;
+        let from_currency = from_currency.to_ascii_uppercase();
+        let to_currency = to_currency.to_ascii_uppercase();
+        if !is_currency_code(&from_currency)
+            || !is_currency_code(&to_currency)
+            || from_currency == to_currency
+        {
+            return Err(format!("Line {}: invalid currency pair", index + 1));
+        }
+        let rate = rate
+            .parse::<Decimal>()
+            .ok()
+            .and_then(check_rate)
+            .ok_or_else(|| format!("Line {}: invalid rate", index + 1))?;
+
+        rows.push(RateRow {
+            from_currency,
+            to_currency,
+            rate,
+            effective_on,
+        });
+    }
+    Ok(rows)
+}
+
+async fn latest_rate<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: i32,
+    from: &str,
+    to: &str,
+    on: NaiveDate,
+) -> Result<Option<exchange_rate::Model>, actix_web::Error> {
+    ExchangeRate::find()
+        .filter(exchange_rate::Column::WorkspaceId.eq(workspace_id))
+        .filter(exchange_rate::Column::FromCurrency.eq(from))
+        .filter(exchange_rate::Column::ToCurrency.eq(to))
+        .filter(exchange_rate::Column::EffectiveOn.lte(on))
+        .order_by_desc(exchange_rate::Column::EffectiveOn)
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))
+}
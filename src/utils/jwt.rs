@@ -0,0 +1,67 @@
+use chrono::{Duration, Utc};
+use jsonwebtoken::Validation;
+use serde::{Deserialize, Serialize};
+
+use crate::controllers::auth::Claims;
+use crate::entities::user::Model as UserModel;
+use crate::utils::jwt_keys::KeyRing;
+use crate::utils::responses::unauthorized;
+
+pub const ACCESS_TOKEN_LIFETIME: Duration = Duration::hours(4);
+pub const MFA_CHALLENGE_LIFETIME: Duration = Duration::minutes(5);
+const MFA_AUDIENCE: &str = "spenser:mfa";
+
+/// Claims of the short-lived token returned by the password step of a
+/// two-factor sign-in. It only proves the password was correct and cannot
+/// be used as an access token.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChallengeClaims {
+    pub sub: i32,
+    pub aud: String,
+    pub exp: i64,
+}
+
+pub fn issue_access_token(
+    keys: &KeyRing,
+    user: &UserModel,
+    session_id: &str,
+    jti: &str,
+) -> Result<String, actix_web::Error> {
+    let claims = Claims {
+        sub: user.id,
+        role: user.role,
+        exp: (Utc::now() + ACCESS_TOKEN_LIFETIME).timestamp(),
+        sid: session_id.to_string(),
+        jti: jti.to_string(),
+    };
+
+    keys.sign(&claims)
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("JWT error: {}", e)))
+}
+
+pub fn validate_access_token(token: &str, keys: &KeyRing) -> Result<Claims, actix_web::Error> {
+    keys.verify::<Claims>(token, Validation::default())
+        .map_err(|_| unauthorized("Invalid token"))
+}
+
+pub fn issue_challenge_token(keys: &KeyRing, user_id: i32) -> Result<String, actix_web::Error> {
+    let claims = ChallengeClaims {
+        sub: user_id,
+        aud: MFA_AUDIENCE.to_string(),
+        exp: (Utc::now() + MFA_CHALLENGE_LIFETIME).timestamp(),
+    };
+
+    keys.sign(&claims)
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("JWT error: {}", e)))
+}
+
+pub fn validate_challenge_token(
+    token: &str,
+    keys: &KeyRing,
+) -> Result<ChallengeClaims, actix_web::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[MFA_AUDIENCE]);
+
+    keys.verify::<ChallengeClaims>(token, validation)
+        .map_err(|_| unauthorized("Invalid or expired challenge token"))
+}
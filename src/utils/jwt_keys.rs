@@ -0,0 +1,218 @@
+use std::{env, fs, io, path::Path};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+    errors::{Error as JwtError, ErrorKind},
+};
+use rsa::{RsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+
+/// DER prefix of an Ed25519 `SubjectPublicKeyInfo`; the raw key follows.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+struct SigningKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: EncodingKey,
+}
+
+struct VerifyingKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+    /// Public JWK, published on the JWKS endpoint. `None` for the HS256 secret.
+    jwk: Option<Value>,
+}
+
+/// Keys used to sign and verify JWTs.
+///
+/// Asymmetric keys are read from `JWT_KEYS_DIR`: every `<kid>.pub.pem` is
+/// accepted for verification, and a matching `<kid>.key.pem` makes the key
+/// usable for signing. `JWT_ACTIVE_KID` picks the signing key, defaulting to
+/// the last kid in sort order. Tokens carry the signing key's id in their
+/// `kid` header.
+///
+/// `JWT_SECRET`, when set, is still accepted for HS256 tokens without a `kid`
+/// and is used for signing if no asymmetric key is available.
+pub struct KeyRing {
+    signing: SigningKey,
+    verifying: Vec<VerifyingKey>,
+}
+
+impl KeyRing {
+    pub fn from_env() -> io::Result<Self> {
+        let mut signing = Vec::new();
+        let mut verifying = Vec::new();
+
+        if let Some(dir) = var("JWT_KEYS_DIR") {
+            load_dir(Path::new(&dir), &mut signing, &mut verifying)?;
+        }
+
+        if let Some(secret) = var("JWT_SECRET") {
+            signing.push(SigningKey {
+                kid: None,
+                algorithm: Algorithm::HS256,
+                key: EncodingKey::from_secret(secret.as_bytes()),
+            });
+            verifying.push(VerifyingKey {
+                kid: None,
+                algorithm: Algorithm::HS256,
+                key: DecodingKey::from_secret(secret.as_bytes()),
+                jwk: None,
+            });
+        }
+
+        let active = match var("JWT_ACTIVE_KID") {
+            Some(kid) => signing
+                .iter()
+                .position(|key| key.kid.as_deref() == Some(kid.as_str()))
+                .ok_or_else(|| {
+                    io::Error::other(format!(
+                        "❌ JWT_ACTIVE_KID '{}' has no private key in JWT_KEYS_DIR",
+                        kid
+                    ))
+                })?,
+            None => signing
+                .iter()
+                .rposition(|key| key.kid.is_some())
+                .or_else(|| signing.iter().position(|key| key.kid.is_none()))
+                .ok_or_else(|| {
+                    io::Error::other(
+                        "❌ Neither JWT_KEYS_DIR nor JWT_SECRET provides a signing key",
+                    )
+                })?,
+        };
+        let signing = signing.swap_remove(active);
+
+        println!(
+            "✅ JWT keys loaded: signing with {} ({:?}), {} verification key(s)",
+            signing.kid.as_deref().unwrap_or("JWT_SECRET"),
+            signing.algorithm,
+            verifying.len()
+        );
+
+        Ok(Self { signing, verifying })
+    }
+
+    /// Signs `claims` with the active key.
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, JwtError> {
+        let mut header = Header::new(self.signing.algorithm);
+        header.kid = self.signing.kid.clone();
+        encode(&header, claims, &self.signing.key)
+    }
+
+    /// Verifies `token` against the key named by its `kid` header, or the
+    /// HS256 secret for tokens without one. `validation` supplies everything
+    /// but the algorithm, which is pinned to the key's.
+    pub fn verify<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        mut validation: Validation,
+    ) -> Result<T, JwtError> {
+        let header = decode_header(token)?;
+        let key = self
+            .verifying
+            .iter()
+            .find(|key| key.kid == header.kid && key.algorithm == header.alg)
+            .ok_or_else(|| JwtError::from(ErrorKind::InvalidSignature))?;
+
+        validation.algorithms = vec![key.algorithm];
+        decode::<T>(token, &key.key, &validation).map(|token_data| token_data.claims)
+    }
+
+    /// Public keys as a JSON Web Key Set.
+    pub fn jwks(&self) -> Value {
+        let keys: Vec<&Value> = self
+            .verifying
+            .iter()
+            .filter_map(|key| key.jwk.as_ref())
+            .collect();
+        json!({ "keys": keys })
+    }
+}
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn load_dir(
+    dir: &Path,
+    signing: &mut Vec<SigningKey>,
+    verifying: &mut Vec<VerifyingKey>,
+) -> io::Result<()> {
+    let mut kids: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(".pub.pem"))
+                .map(String::from)
+        })
+        .collect();
+    kids.sort();
+
+    for kid in kids {
+        let public_pem = fs::read(dir.join(format!("{}.pub.pem", kid)))?;
+        let (algorithm, key, jwk) = parse_public_key(&kid, &public_pem)?;
+        verifying.push(VerifyingKey {
+            kid: Some(kid.clone()),
+            algorithm,
+            key,
+            jwk: Some(jwk),
+        });
+
+        let private_path = dir.join(format!("{}.key.pem", kid));
+        if private_path.exists() {
+            let private_pem = fs::read(private_path)?;
+            let key = match algorithm {
+                Algorithm::EdDSA => EncodingKey::from_ed_pem(&private_pem),
+                _ => EncodingKey::from_rsa_pem(&private_pem),
+            }
+            .map_err(|e| io::Error::other(format!("❌ Invalid private key '{}': {}", kid, e)))?;
+            signing.push(SigningKey {
+                kid: Some(kid),
+                algorithm,
+                key,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Reads a PEM `SubjectPublicKeyInfo` holding an Ed25519 or RSA key.
+fn parse_public_key(kid: &str, pem_bytes: &[u8]) -> io::Result<(Algorithm, DecodingKey, Value)> {
+    let invalid = |e: String| io::Error::other(format!("❌ Invalid public key '{}': {}", kid, e));
+
+    let der = pem::parse(pem_bytes).map_err(|e| invalid(e.to_string()))?;
+    let der = der.contents();
+
+    if der.len() == ED25519_SPKI_PREFIX.len() + 32 && der.starts_with(&ED25519_SPKI_PREFIX) {
+        let raw = &der[ED25519_SPKI_PREFIX.len()..];
+        let jwk = json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "use": "sig",
+            "alg": "EdDSA",
+            "kid": kid,
+            "x": URL_SAFE_NO_PAD.encode(raw),
+        });
+        return Ok((Algorithm::EdDSA, DecodingKey::from_ed_der(raw), jwk));
+    }
+
+    let public_key = RsaPublicKey::from_public_key_der(der).map_err(|e| invalid(e.to_string()))?;
+    let key = DecodingKey::from_rsa_pem(pem_bytes).map_err(|e| invalid(e.to_string()))?;
+    let jwk = json!({
+        "kty": "RSA",
+        "use": "sig",
+        "alg": "RS256",
+        "kid": kid,
+        "n": URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+        "e": URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+    });
+    Ok((Algorithm::RS256, key, jwk))
+}
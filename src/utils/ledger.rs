@@ -0,0 +1,504 @@
+//! Ledger writes and the row locks that keep them consistent.
+//!
+//! Every database transaction that moves money or deletes ledger rows takes
+//! its locks in the same order, so concurrent writers queue up instead of
+//! deadlocking:
+//!
+//! 1. the workspace row, with `lock_workspace` or `lock_balances`;
+//! 2. transaction rows, with `SELECT ... FOR UPDATE`;
+//! 3. accounts, then categories, each in id order, with `lock_balances`.
+//!
+//! Inserts into `transaction`, `journal_entry` and `posting` happen only
+//! while the workspace row is locked.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, sea_query::Expr,
+};
+use serde_json::json;
+
+use crate::entities::{
+    account::{self, Entity as Account, Model as AccountModel},
+    category::{self, Entity as Category, Model as CategoryModel},
+    journal_entry::{
+        self, ActiveModel as JournalEntryActiveModel, Entity as JournalEntry,
+        Model as JournalEntryModel,
+    },
+    posting::{ActiveModel as PostingActiveModel, Entity as Posting},
+    sea_orm_active_enums::{AccountKind, TransactionKind},
+    transaction::{self, Entity as Transaction, Model as TransactionModel},
+    workspace::{self, Entity as Workspace, Model as WorkspaceModel},
+};
+use crate::utils::workspaces::WorkspaceAccess;
+
+/// Balance change of one transaction row on its account and category. In the
+/// journal a leg becomes two postings that cancel out: `delta` on the account
+/// and `-delta` on the category. `base_delta` is the same change in the
+/// workspace's base currency.
+pub struct Leg {
+    pub account_id: i32,
+    pub category_id: i32,
+    pub delta: i64,
+    pub base_delta: i64,
+}
+
+impl Leg {
+    /// The leg that undoes `transaction`.
+    pub fn reversal(transaction: &TransactionModel) -> Result<Self, actix_web::Error> {
+        Ok(Self {
+            account_id: transaction.account_id,
+            category_id: transaction.category_id,
+            delta: -leg_delta(transaction.r#type, transaction.amount)?,
+            base_delta: -leg_delta(transaction.r#type, transaction.base_amount)?,
+        })
+    }
+}
+
+/// Workspace, account and category rows locked for a ledger write.
+pub struct LockedBalances {
+    workspace: WorkspaceModel,
+    accounts: HashMap<i32, AccountModel>,
+    categories: HashMap<i32, CategoryModel>,
+}
+
+impl LockedBalances {
+    pub fn base_currency(&self) -> &str {
+        &self.workspace.base_currency
+    }
+
+    /// Currency of a leg. The account and the category must agree on it, so
+    /// each journal entry balances within a single currency.
+    pub fn currency(&self, account_id: i32, category_id: i32) -> Result<&str, actix_web::Error> {
+        let currency = &self.accounts[&account_id].currency;
+        if *currency != self.categories[&category_id].currency {
+            return Err(actix_web::error::ErrorBadRequest(json!({
+                "status": "error",
+                "message": "The account and the category must use the same currency"
+            })));
+        }
+        Ok(currency)
+    }
+
+    /// Rejects `legs` when their combined effect would take an account or a
+    /// category below zero, or any balance out of range. Credit cards may run
+    /// a negative balance.
+    pub fn ensure_covered(&self, legs: &[Leg]) -> Result<(), actix_web::Error> {
+        let mut account_deltas: HashMap<i32, i64> = HashMap::new();
+        let mut category_deltas: HashMap<i32, i64> = HashMap::new();
+        let mut base_delta: i64 = 0;
+        for leg in legs {
+            add_to(account_deltas.entry(leg.account_id).or_insert(0), leg.delta)?;
+            add_to(
+                category_deltas.entry(leg.category_id).or_insert(0),
+                leg.delta,
+            )?;
+            add_to(&mut base_delta, leg.base_delta)?;
+        }
+        let mut workspace_balance = self.workspace.balance;
+        add_to(&mut workspace_balance, base_delta)?;
+
+        let mut accounts_covered = true;
+        for (id, delta) in &account_deltas {
+            let account = &self.accounts[id];
+            let mut balance = account.balance;
+            add_to(&mut balance, *delta)?;
+            accounts_covered &= account.kind == AccountKind::CreditCard || balance >= 0;
+        }
+        let mut categories_covered = true;
+        for (id, delta) in &category_deltas {
+            let mut balance = self.categories[id].balance;
+            add_to(&mut balance, *delta)?;
+            categories_covered &= balance >= 0;
+        }
+
+        if !accounts_covered || !categories_covered {
+            return Err(actix_web::error::ErrorBadRequest(
+                json!({"status": "error", "message": "Insufficient balance"}),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Locks the workspace row with `SELECT ... FOR UPDATE`, the first lock of
+/// every ledger write. Callers that lock transaction rows take it before them.
+pub async fn lock_workspace<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
+) -> Result<WorkspaceModel, actix_web::Error> {
+    Workspace::find_by_id(access.workspace_id)
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(
+                json!({"status": "error", "message": "Workspace not found"}),
+            )
+        })
+}
+
+/// Loads the given accounts and categories with `SELECT ... FOR UPDATE`, so
+/// balance checks and the following balance updates see a consistent snapshot.
+/// Locks are taken in the order set out at the top of this module.
+pub async fn lock_balances<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
+    account_ids: &[i32],
+    category_ids: &[i32],
+) -> Result<LockedBalances, actix_web::Error> {
+    // Such grants cannot see accounts, so they cannot move money through them
+    if !account_ids.is_empty() {
+        access.require_whole_workspace(
+            "Access grants limited to some categories cannot move money through accounts",
+        )?;
+    }
+    let workspace = lock_workspace(db, access).await?;
+
+    let account_ids: BTreeSet<i32> = account_ids.iter().copied().collect();
+    let accounts: HashMap<i32, AccountModel> = Account::find()
+        .filter(account::Column::Id.is_in(account_ids.iter().copied()))
+        .filter(account::Column::WorkspaceId.eq(access.workspace_id))
+        .order_by_asc(account::Column::Id)
+        .lock_exclusive()
+        .all(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .into_iter()
+        .map(|account| (account.id, account))
+        .collect();
+    if accounts.len() != account_ids.len() {
+        return Err(actix_web::error::ErrorNotFound(
+            json!({"status": "error", "message": "Account not found or unauthorized"}),
+        ));
+    }
+
+    let category_ids: BTreeSet<i32> = category_ids.iter().copied().collect();
+    let categories: HashMap<i32, CategoryModel> = Category::find()
+        .filter(category::Column::Id.is_in(category_ids.iter().copied()))
+        .filter(category::Column::WorkspaceId.eq(access.workspace_id))
+        .order_by_asc(category::Column::Id)
+        .lock_exclusive()
+        .all(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .into_iter()
+        .filter(|category| access.allows_category(category.id))
+        .map(|category| (category.id, category))
+        .collect();
+    if categories.len() != category_ids.len() {
+        return Err(actix_web::error::ErrorNotFound(
+            json!({"status": "error", "message": "Category not found or unauthorized"}),
+        ));
+    }
+
+    Ok(LockedBalances {
+        workspace,
+        accounts,
+        categories,
+    })
+}
+
+/// Writes `legs` to the journal as a single entry and applies them to the
+/// balances. Callers link their transaction rows to the returned entry. Must
+/// run inside a database transaction, after `lock_balances`.
+pub async fn record_entry<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
+    legs: &[Leg],
+) -> Result<JournalEntryModel, actix_web::Error> {
+    let entry = JournalEntryActiveModel {
+        workspace_id: Set(access.workspace_id),
+        user_id: Set(Some(access.user_id)),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    let postings = legs.iter().flat_map(|leg| {
+        [
+            PostingActiveModel {
+                journal_entry_id: Set(entry.id),
+                account_id: Set(Some(leg.account_id)),
+                amount: Set(leg.delta),
+                base_amount: Set(leg.base_delta),
+                ..Default::default()
+            },
+            PostingActiveModel {
+                journal_entry_id: Set(entry.id),
+                category_id: Set(Some(leg.category_id)),
+                amount: Set(-leg.delta),
+                base_amount: Set(-leg.base_delta),
+                ..Default::default()
+            },
+        ]
+    });
+    Posting::insert_many(postings).exec(db).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    for leg in legs {
+        apply_leg(db, access.workspace_id, leg).await?;
+    }
+    Ok(entry)
+}
+
+/// Applies the balance effect of a leg with `balance = balance + ?` updates.
+/// The workspace balance is kept in its base currency. Must run inside the
+/// same database transaction as the ledger write.
+async fn apply_leg<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: i32,
+    leg: &Leg,
+) -> Result<(), actix_web::Error> {
+    Workspace::update_many()
+        .col_expr(
+            workspace::Column::Balance,
+            Expr::col(workspace::Column::Balance).add(leg.base_delta),
+        )
+        .filter(workspace::Column::Id.eq(workspace_id))
+        .exec(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Account::update_many()
+        .col_expr(
+            account::Column::Balance,
+            Expr::col(account::Column::Balance).add(leg.delta),
+        )
+        .filter(account::Column::Id.eq(leg.account_id))
+        .exec(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Category::update_many()
+        .col_expr(
+            category::Column::Balance,
+            Expr::col(category::Column::Balance).add(leg.delta),
+        )
+        .filter(category::Column::Id.eq(leg.category_id))
+        .exec(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(())
+}
+
+/// Deletes `transactions` with their journal entries and reverses their effect
+/// on the balances, after checking that the balances can give the money back.
+/// Transactions sharing an entry, like the legs of a transfer, must be
+/// deleted together.
+pub async fn delete_legs<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
+    transactions: &[TransactionModel],
+) -> Result<(), actix_web::Error> {
+    let legs = transactions
+        .iter()
+        .map(Leg::reversal)
+        .collect::<Result<Vec<Leg>, actix_web::Error>>()?;
+    let account_ids: Vec<i32> = legs.iter().map(|leg| leg.account_id).collect();
+    let category_ids: Vec<i32> = legs.iter().map(|leg| leg.category_id).collect();
+    lock_balances(db, access, &account_ids, &category_ids)
+        .await?
+        .ensure_covered(&legs)?;
+
+    // Entries cascade to their postings and transaction rows. An entry that is
+    // already gone was reversed by a concurrent delete, so reversing it again
+    // would count it twice.
+    let entry_ids: BTreeSet<i32> = transactions.iter().map(|t| t.journal_entry_id).collect();
+    let deleted = JournalEntry::delete_many()
+        .filter(journal_entry::Column::Id.is_in(entry_ids.iter().copied()))
+        .exec(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if deleted.rows_affected != entry_ids.len() as u64 {
+        return Err(actix_web::error::ErrorNotFound(
+            json!({"status": "error", "message": "Not found or unauthorized"}),
+        ));
+    }
+    for leg in &legs {
+        apply_leg(db, access.workspace_id, leg).await?;
+    }
+    Ok(())
+}
+
+/// Re-records `transaction` as `leg`: writes a new journal entry, points the
+/// transaction at it, then deletes the old entry and reverses its effect on
+/// the balances. Must run inside a database transaction, after
+/// `ensure_covered` has checked the reversal and `leg` together.
+pub async fn replace_entry<C: ConnectionTrait>(
+    db: &C,
+    access: &WorkspaceAccess,
+    transaction: &TransactionModel,
+    leg: &Leg,
+) -> Result<JournalEntryModel, actix_web::Error> {
+    let reversal = Leg::reversal(transaction)?;
+    let entry = record_entry(db, access, std::slice::from_ref(leg)).await?;
+
+    // Repoint first, so the cascade does not take the transaction along
+    Transaction::update_many()
+        .col_expr(transaction::Column::JournalEntryId, Expr::value(entry.id))
+        .filter(transaction::Column::Id.eq(transaction.id))
+        .exec(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    let deleted = JournalEntry::delete_by_id(transaction.journal_entry_id)
+        .exec(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+    if deleted.rows_affected != 1 {
+        return Err(actix_web::error::ErrorNotFound(
+            json!({"status": "error", "message": "Not found or unauthorized"}),
+        ));
+    }
+    apply_leg(db, access.workspace_id, &reversal).await?;
+    Ok(entry)
+}
+
+/// Adds `delta` to `total`, failing instead of overflowing.
+fn add_to(total: &mut i64, delta: i64) -> Result<(), actix_web::Error> {
+    *total = total.checked_add(delta).ok_or_else(|| {
+        actix_web::error::ErrorBadRequest(
+            json!({"status": "error", "message": "Balance out of range"}),
+        )
+    })?;
+    Ok(())
+}
+
+/// Signed balance effect of a transaction, failing instead of overflowing.
+pub fn leg_delta(kind: TransactionKind, amount: i64) -> Result<i64, actix_web::Error> {
+    kind.balance_delta(amount).ok_or_else(|| {
+        actix_web::error::ErrorBadRequest(
+            json!({"status": "error", "message": "Balance out of range"}),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: i32, kind: AccountKind, balance: i64) -> AccountModel {
+        AccountModel {
+            id,
+            workspace_id: 1,
+            user_id: None,
+            name: format!("Account {}", id),
+            kind,
+            currency: "USD".to_string(),
+            balance,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn category(id: i32, balance: i64) -> CategoryModel {
+        CategoryModel {
+            id,
+            workspace_id: 1,
+            user_id: None,
+            name: format!("Category {}", id),
+            description: None,
+            balance,
+            currency: "USD".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Checking account 1 holds 100, credit card 2 is at 0; category 10 holds
+    /// 60 and category 11 holds 40.
+    fn locked() -> LockedBalances {
+        LockedBalances {
+            workspace: WorkspaceModel {
+                id: 1,
+                name: "Home".to_string(),
+                balance: 100,
+                base_currency: "USD".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            accounts: HashMap::from([
+                (1, account(1, AccountKind::Checking, 100)),
+                (2, account(2, AccountKind::CreditCard, 0)),
+            ]),
+            categories: HashMap::from([(10, category(10, 60)), (11, category(11, 40))]),
+        }
+    }
+
+    fn leg(account_id: i32, category_id: i32, delta: i64) -> Leg {
+        Leg {
+            account_id,
+            category_id,
+            delta,
+            base_delta: delta,
+        }
+    }
+
+    #[test]
+    fn allows_spending_the_whole_balance() {
+        assert!(locked().ensure_covered(&[leg(1, 10, -60)]).is_ok());
+    }
+
+    #[test]
+    fn rejects_overdrawing_a_category() {
+        assert!(locked().ensure_covered(&[leg(1, 11, -41)]).is_err());
+    }
+
+    #[test]
+    fn rejects_overdrawing_a_checking_account() {
+        let mut locked = locked();
+        locked.categories.get_mut(&10).unwrap().balance = 500;
+        assert!(locked.ensure_covered(&[leg(1, 10, -101)]).is_err());
+    }
+
+    #[test]
+    fn lets_credit_cards_go_negative() {
+        assert!(locked().ensure_covered(&[leg(2, 10, -50)]).is_ok());
+    }
+
+    #[test]
+    fn checks_the_combined_effect_of_all_legs() {
+        // Moving 50 from category 10 to category 11 leaves the account as is
+        assert!(
+            locked()
+                .ensure_covered(&[leg(1, 10, -50), leg(1, 11, 50)])
+                .is_ok()
+        );
+        // Each leg alone fits category 10, both together do not
+        assert!(
+            locked()
+                .ensure_covered(&[leg(1, 10, -40), leg(1, 10, -40)])
+                .is_err()
+        );
+        // A reversal can free up the money a replacement needs
+        assert!(
+            locked()
+                .ensure_covered(&[leg(1, 11, 40), leg(1, 11, -80)])
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_balances_out_of_range() {
+        assert!(locked().ensure_covered(&[leg(1, 10, i64::MAX)]).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use actix_web::{HttpResponse, error::InternalError};
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QuerySelect, Set, sea_query::Expr,
+};
+use serde_json::json;
+use std::env;
+
+use crate::entities::{
+    login_attempt::{self, ActiveModel as LoginAttemptActiveModel, Entity as LoginAttempt},
+    user::{self, Entity as User, Model as UserModel},
+};
+
+const BASE_DELAY_MS: i64 = 250;
+const MAX_DELAY_MS: i64 = 8_000;
+
+/// Limits applied to `/auth/sign-in`, read from the environment at startup.
+pub struct LoginPolicy {
+    /// Consecutive failures after which an account is locked.
+    pub max_failures: i32,
+    /// How long a locked account stays locked.
+    pub lockout: Duration,
+    /// Failures allowed from one IP address within `ip_window`.
+    pub ip_max_failures: u64,
+    pub ip_window: Duration,
+}
+
+impl LoginPolicy {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            max_failures: var("LOGIN_MAX_FAILURES", 5),
+            lockout: Duration::minutes(var("LOGIN_LOCKOUT_MINUTES", 15)),
+            ip_max_failures: var("LOGIN_IP_MAX_FAILURES", 20),
+            ip_window: Duration::minutes(var("LOGIN_IP_WINDOW_MINUTES", 15)),
+        }
+    }
+}
+
+/// Rejects the request when `ip` has exceeded its failure budget.
+pub async fn check_ip<C: ConnectionTrait>(
+    db: &C,
+    policy: &LoginPolicy,
+    ip: &str,
+) -> Result<(), actix_web::Error> {
+    let failures = LoginAttempt::find()
+        .filter(login_attempt::Column::Ip.eq(ip))
+        .filter(login_attempt::Column::Succeeded.eq(false))
+        .filter(login_attempt::Column::CreatedAt.gt(Utc::now() - policy.ip_window))
+        .count(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    if failures >= policy.ip_max_failures {
+        return Err(too_many_attempts(policy.ip_window));
+    }
+    Ok(())
+}
+
+/// Rejects the request while `user` is locked out.
+pub fn check_not_locked(user: &UserModel) -> Result<(), actix_web::Error> {
+    match user.locked_until {
+        Some(locked_until) if locked_until > Utc::now() => {
+            Err(too_many_attempts(locked_until - Utc::now()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Sleeps before checking credentials, doubling the delay with every recent
+/// failure for `email` so guessing gets progressively slower.
+pub async fn throttle<C: ConnectionTrait>(
+    db: &C,
+    policy: &LoginPolicy,
+    email: &str,
+) -> Result<(), actix_web::Error> {
+    let failures = LoginAttempt::find()
+        .filter(login_attempt::Column::Email.eq(email))
+        .filter(login_attempt::Column::Succeeded.eq(false))
+        .filter(login_attempt::Column::CreatedAt.gt(Utc::now() - policy.ip_window))
+        .count(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+
+    if failures > 0 {
+        let delay = BASE_DELAY_MS
+            .saturating_mul(1 << (failures - 1).min(16))
+            .min(MAX_DELAY_MS);
+        tokio::time::sleep(std::time::Duration::from_millis(delay as u64)).await;
+    }
+    Ok(())
+}
+
+/// Records a failed attempt and locks the account once it reaches
+/// `policy.max_failures` consecutive failures. The counter is incremented in
+/// the database and read back, so concurrent attempts cannot all act on the
+/// same stale count.
+pub async fn record_failure<C: ConnectionTrait>(
+    db: &C,
+    policy: &LoginPolicy,
+    email: &str,
+    ip: &str,
+    user: Option<&UserModel>,
+) -> Result<(), DbErr> {
+    insert_attempt(db, email, ip, user.map(|user| user.id), false).await?;
+
+    let Some(user) = user else {
+        return Ok(());
+    };
+
+    User::update_many()
+        .col_expr(
+            user::Column::FailedLoginCount,
+            Expr::col(user::Column::FailedLoginCount).add(1),
+        )
+        .filter(user::Column::Id.eq(user.id))
+        .exec(db)
+        .await?;
+    let failures: Option<i32> = User::find_by_id(user.id)
+        .select_only()
+        .column(user::Column::FailedLoginCount)
+        .into_tuple()
+        .one(db)
+        .await?;
+
+    if failures.is_some_and(|failures| failures >= policy.max_failures) {
+        User::update_many()
+            .col_expr(
+                user::Column::LockedUntil,
+                Expr::value(Utc::now() + policy.lockout),
+            )
+            .col_expr(user::Column::FailedLoginCount, Expr::value(0))
+            .filter(user::Column::Id.eq(user.id))
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Records a successful attempt and clears the failure counter.
+pub async fn record_success<C: ConnectionTrait>(
+    db: &C,
+    email: &str,
+    ip: &str,
+    user_id: i32,
+) -> Result<(), DbErr> {
+    insert_attempt(db, email, ip, Some(user_id), true).await?;
+    unlock(db, user_id).await
+}
+
+/// Lifts a lockout and resets the failure counter.
+pub async fn unlock<C: ConnectionTrait>(db: &C, user_id: i32) -> Result<(), DbErr> {
+    User::update_many()
+        .col_expr(user::Column::FailedLoginCount, Expr::value(0))
+        .col_expr(
+            user::Column::LockedUntil,
+            Expr::value(Option::<chrono::DateTime<Utc>>::None),
+        )
+        .filter(user::Column::Id.eq(user_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+async fn insert_attempt<C: ConnectionTrait>(
+    db: &C,
+    email: &str,
+    ip: &str,
+    user_id: Option<i32>,
+    succeeded: bool,
+) -> Result<(), DbErr> {
+    LoginAttemptActiveModel {
+        user_id: Set(user_id),
+        email: Set(email.to_string()),
+        ip: Set(ip.to_string()),
+        succeeded: Set(succeeded),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+fn too_many_attempts(retry_after: Duration) -> actix_web::Error {
+    let response = HttpResponse::TooManyRequests()
+        .insert_header(("Retry-After", retry_after.num_seconds().max(1).to_string()))
+        .json(json!({
+            "status": "error",
+            "message": "Too many failed sign-in attempts. Try again later."
+        }));
+    InternalError::from_response("Too many failed sign-in attempts", response).into()
+}
@@ -1,3 +1,21 @@
 pub mod responses;
+pub mod access_grants;
 pub mod db;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod balances;
+pub mod cookies;
+pub mod exchange_rates;
+pub mod jwt;
+pub mod jwt_keys;
+pub mod ledger;
+pub mod login_attempts;
+pub mod money;
+pub mod oidc;
+pub mod one_time_tokens;
+pub mod password;
+pub mod personal_access_tokens;
+pub mod sessions;
+pub mod tokens;
+pub mod totp;
+pub mod validation;
+pub mod workspaces;
@@ -0,0 +1,234 @@
+use std::fmt;
+
+use rust_decimal::{Decimal, RoundingStrategy, prelude::ToPrimitive};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de, ser::SerializeStruct};
+
+use crate::utils::exchange_rates::is_currency_code;
+
+/// ISO 4217 currencies without minor units.
+const ZERO_DECIMAL_CURRENCIES: &[&str] = &[
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "UYI", "VND",
+    "VUV", "XAF", "XOF", "XPF",
+];
+/// ISO 4217 currencies with three decimal places.
+const THREE_DECIMAL_CURRENCIES: &[&str] = &["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+/// ISO 4217 currencies with four decimal places.
+const FOUR_DECIMAL_CURRENCIES: &[&str] = &["CLF", "UYW"];
+
+/// Number of decimal places of `currency`, e.g. 0 for `JPY`, 2 for `USD` and
+/// 3 for `BHD`.
+pub fn exponent(currency: &str) -> u32 {
+    if ZERO_DECIMAL_CURRENCIES.contains(&currency) {
+        0
+    } else if THREE_DECIMAL_CURRENCIES.contains(&currency) {
+        3
+    } else if FOUR_DECIMAL_CURRENCIES.contains(&currency) {
+        4
+    } else {
+        2
+    }
+}
+
+/// An amount in the minor units of its currency, such as cents for `USD`.
+/// Amounts are stored as minor units and exchanged with clients as a decimal
+/// string plus currency: `{"amount": "12.34", "currency": "USD"}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Money {
+    minor_units: i64,
+    currency: String,
+}
+
+impl Money {
+    pub fn new(minor_units: i64, currency: impl Into<String>) -> Self {
+        Self {
+            minor_units,
+            currency: currency.into(),
+        }
+    }
+
+    pub fn minor_units(&self) -> i64 {
+        self.minor_units
+    }
+
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.minor_units > 0
+    }
+
+    /// Parses a decimal string such as `"12.34"`. Fails when the amount has
+    /// more decimal places than the currency or does not fit.
+    pub fn parse(amount: &str, currency: &str) -> Result<Self, String> {
+        let currency = currency.trim().to_ascii_uppercase();
+        if !is_currency_code(&currency) {
+            return Err("currency must be a three-letter ISO 4217 code".to_string());
+        }
+        let amount: Decimal = amount
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid amount: {}", amount))?;
+        let amount = amount.normalize();
+        let exponent = exponent(&currency);
+        if amount.scale() > exponent {
+            return Err(format!(
+                "{} amounts have at most {} decimal places",
+                currency, exponent
+            ));
+        }
+
+        let minor_units = amount
+            .checked_mul(Decimal::from(10i64.pow(exponent)))
+            .and_then(|minor_units| minor_units.to_i64())
+            .ok_or_else(|| "amount is too large".to_string())?;
+        Ok(Self::new(minor_units, currency))
+    }
+
+    /// Converts to `currency` at `rate` units of it per unit of this amount's
+    /// currency, rounding half away from zero to the minor units of `currency`.
+    pub fn convert(&self, rate: Decimal, currency: &str) -> Option<Money> {
+        self.to_decimal()
+            .checked_mul(rate)?
+            .round_dp_with_strategy(exponent(currency), RoundingStrategy::MidpointAwayFromZero)
+            .checked_mul(Decimal::from(10i64.pow(exponent(currency))))?
+            .to_i64()
+            .map(|minor_units| Self::new(minor_units, currency))
+    }
+
+    fn to_decimal(&self) -> Decimal {
+        Decimal::new(self.minor_units, exponent(&self.currency))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_decimal())
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut money = serializer.serialize_struct("Money", 2)?;
+        money.serialize_field("amount", &self.to_string())?;
+        money.serialize_field("currency", &self.currency)?;
+        money.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields {
+            amount: String,
+            currency: String,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        Money::parse(&fields.amount, &fields.currency).map_err(de::Error::custom)
+    }
+}
+
+/// Deserializes an amount that must be greater than zero, for request fields.
+pub fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+    let money = Money::deserialize(deserializer)?;
+    if !money.is_positive() {
+        return Err(de::Error::custom("amount must be positive"));
+    }
+    Ok(money)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn exponents_follow_iso_4217() {
+        assert_eq!(exponent("JPY"), 0);
+        assert_eq!(exponent("USD"), 2);
+        assert_eq!(exponent("BHD"), 3);
+        assert_eq!(exponent("CLF"), 4);
+    }
+
+    #[test]
+    fn parses_amounts_into_minor_units() {
+        assert_eq!(Money::parse("12.34", "USD"), Ok(Money::new(1234, "USD")));
+        assert_eq!(Money::parse("1.50", "usd"), Ok(Money::new(150, "USD")));
+        assert_eq!(Money::parse("500", "JPY"), Ok(Money::new(500, "JPY")));
+        assert_eq!(Money::parse("1.234", "BHD"), Ok(Money::new(1234, "BHD")));
+        assert_eq!(Money::parse("-0.05", "EUR"), Ok(Money::new(-5, "EUR")));
+    }
+
+    #[test]
+    fn rejects_invalid_amounts() {
+        assert!(Money::parse("12.345", "USD").is_err());
+        assert!(Money::parse("1.5", "JPY").is_err());
+        assert!(Money::parse("abc", "USD").is_err());
+        assert!(Money::parse("1", "US").is_err());
+        assert!(Money::parse("100000000000000000", "USD").is_err());
+    }
+
+    #[test]
+    fn formats_with_the_currency_exponent() {
+        assert_eq!(Money::new(1234, "USD").to_string(), "12.34");
+        assert_eq!(Money::new(150, "USD").to_string(), "1.50");
+        assert_eq!(Money::new(-5, "EUR").to_string(), "-0.05");
+        assert_eq!(Money::new(500, "JPY").to_string(), "500");
+        assert_eq!(Money::new(1234, "BHD").to_string(), "1.234");
+    }
+
+    #[test]
+    fn converts_rounding_half_away_from_zero() {
+        let rate = Decimal::new(5, 1);
+        assert_eq!(
+            Money::new(5, "USD").convert(rate, "EUR"),
+            Some(Money::new(3, "EUR"))
+        );
+        assert_eq!(
+            Money::new(-5, "USD").convert(rate, "EUR"),
+            Some(Money::new(-3, "EUR"))
+        );
+        assert_eq!(
+            Money::new(100, "JPY").convert(Decimal::new(67, 4), "USD"),
+            Some(Money::new(67, "USD"))
+        );
+        assert_eq!(
+            Money::new(i64::MAX, "USD").convert(Decimal::from(1000), "EUR"),
+            None
+        );
+    }
+
+    #[test]
+    fn serializes_as_decimal_string_and_currency() {
+        assert_eq!(
+            serde_json::to_value(Money::new(1234, "USD")).unwrap(),
+            json!({"amount": "12.34", "currency": "USD"})
+        );
+        let money: Money =
+            serde_json::from_value(json!({"amount": "12.34", "currency": "USD"})).unwrap();
+        assert_eq!(money, Money::new(1234, "USD"));
+        assert!(
+            serde_json::from_value::<Money>(json!({"amount": 12.34, "currency": "USD"})).is_err()
+        );
+    }
+
+    #[test]
+    fn positive_rejects_zero_and_negative_amounts() {
+        #[derive(Deserialize, Debug)]
+        struct Request {
+            #[serde(deserialize_with = "positive")]
+            amount: Money,
+        }
+
+        let request = |amount: &str| {
+            serde_json::from_value::<Request>(
+                json!({"amount": {"amount": amount, "currency": "USD"}}),
+            )
+            .map(|request| request.amount)
+        };
+        assert_eq!(request("0.01").unwrap(), Money::new(1, "USD"));
+        assert!(request("0").is_err());
+        assert!(request("-1").is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use std::{env, io};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header,
+    jwk::{Jwk, JwkSet},
+};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+/// Settings of the OpenID Connect provider, read from the environment at
+/// startup. `None` when `OIDC_ISSUER` is not set.
+pub struct OidcConfig {
+    /// Issuer identifier, without a trailing slash.
+    pub issuer: String,
+    pub client_id: String,
+    client_secret: Option<String>,
+    pub redirect_url: String,
+    pub scopes: String,
+    /// Create a Spenser account on the first sign-in of an unknown identity.
+    pub auto_provision: bool,
+    http: reqwest::Client,
+}
+
+/// The parts of the provider's discovery document Spenser uses.
+#[derive(Deserialize, Debug)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// Claims of a validated ID token.
+#[derive(Deserialize, Debug)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "bool_or_string")]
+    pub email_verified: bool,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub name: Option<String>,
+}
+
+impl OidcConfig {
+    pub fn from_env(app_url: &str) -> io::Result<Option<Self>> {
+        fn var(name: &str) -> Option<String> {
+            env::var(name).ok().filter(|value| !value.is_empty())
+        }
+
+        let Some(issuer) = var("OIDC_ISSUER") else {
+            return Ok(None);
+        };
+        let client_id =
+            var("OIDC_CLIENT_ID").ok_or_else(|| io::Error::other("❌ OIDC_CLIENT_ID not set"))?;
+
+        Ok(Some(Self {
+            issuer: issuer.trim_end_matches('/').to_string(),
+            client_id,
+            client_secret: var("OIDC_CLIENT_SECRET"),
+            redirect_url: var("OIDC_REDIRECT_URL")
+                .unwrap_or_else(|| format!("{}/auth/oidc/callback", app_url)),
+            scopes: var("OIDC_SCOPES").unwrap_or_else(|| "openid email profile".to_string()),
+            auto_provision: var("OIDC_AUTO_PROVISION").is_none_or(|value| value != "false"),
+            http: reqwest::Client::new(),
+        }))
+    }
+
+    /// Fetches the provider's discovery document.
+    pub async fn discover(&self) -> Result<ProviderMetadata, actix_web::Error> {
+        let metadata: ProviderMetadata = self
+            .http
+            .get(format!("{}/.well-known/openid-configuration", self.issuer))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(provider_error)?
+            .json()
+            .await
+            .map_err(provider_error)?;
+
+        if metadata.issuer.trim_end_matches('/') != self.issuer {
+            return Err(provider_error(format!(
+                "discovery document is for issuer '{}'",
+                metadata.issuer
+            )));
+        }
+        Ok(metadata)
+    }
+
+    /// URL of the provider's consent page for a new authorization-code flow.
+    pub fn authorization_url(
+        &self,
+        metadata: &ProviderMetadata,
+        state: &str,
+        nonce: &str,
+        code_verifier: &str,
+    ) -> Result<String, actix_web::Error> {
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+        let mut url = Url::parse(&metadata.authorization_endpoint).map_err(provider_error)?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_url)
+            .append_pair("scope", &self.scopes)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", &code_challenge)
+            .append_pair("code_challenge_method", "S256");
+        Ok(url.to_string())
+    }
+
+    /// Redeems an authorization code and returns the validated ID token claims.
+    pub async fn exchange_code(
+        &self,
+        metadata: &ProviderMetadata,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, actix_web::Error> {
+        let mut request = self.http.post(&metadata.token_endpoint).form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.redirect_url),
+            ("client_id", &self.client_id),
+            ("code_verifier", code_verifier),
+        ]);
+        if let Some(client_secret) = &self.client_secret {
+            request = request.basic_auth(&self.client_id, Some(client_secret));
+        }
+
+        let response = request.send().await.map_err(provider_error)?;
+        if response.status().is_client_error() {
+            eprintln!(
+                "❌ OIDC token endpoint refused the code: {}",
+                response.text().await.unwrap_or_default()
+            );
+            return Err(actix_web::error::ErrorUnauthorized(json!({
+                "status": "error",
+                "message": "The identity provider rejected the authorization code"
+            })));
+        }
+        let tokens: TokenResponse = response
+            .error_for_status()
+            .map_err(provider_error)?
+            .json()
+            .await
+            .map_err(provider_error)?;
+
+        let claims = self.validate_id_token(metadata, &tokens.id_token).await?;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid_id_token("nonce mismatch"));
+        }
+        Ok(claims)
+    }
+
+    async fn validate_id_token(
+        &self,
+        metadata: &ProviderMetadata,
+        id_token: &str,
+    ) -> Result<IdTokenClaims, actix_web::Error> {
+        let header = decode_header(id_token).map_err(|e| invalid_id_token(&e.to_string()))?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(invalid_id_token("symmetric signatures are not accepted"));
+        }
+
+        let jwks: JwkSet = self
+            .http
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(provider_error)?
+            .json()
+            .await
+            .map_err(provider_error)?;
+        let jwk: &Jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        }
+        .ok_or_else(|| invalid_id_token("signing key not found"))?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|e| invalid_id_token(&e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.client_id]);
+
+        decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|token_data| token_data.claims)
+            .map_err(|e| invalid_id_token(&e.to_string()))
+    }
+}
+
+/// Some providers send `email_verified` as the string `"true"`.
+fn bool_or_string<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrString {
+        Bool(bool),
+        String(String),
+    }
+
+    Ok(match BoolOrString::deserialize(deserializer)? {
+        BoolOrString::Bool(value) => value,
+        BoolOrString::String(value) => value == "true",
+    })
+}
+
+fn provider_error(e: impl std::fmt::Display) -> actix_web::Error {
+    eprintln!("❌ OIDC provider error: {}", e);
+    actix_web::error::ErrorBadGateway(json!({
+        "status": "error",
+        "message": "The identity provider could not be reached"
+    }))
+}
+
+fn invalid_id_token(reason: &str) -> actix_web::Error {
+    eprintln!("❌ Rejected OIDC ID token: {}", reason);
+    actix_web::error::ErrorUnauthorized(json!({
+        "status": "error",
+        "message": "Invalid ID token"
+    }))
+}
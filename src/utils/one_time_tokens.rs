@@ -0,0 +1,68 @@
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect,
+    Set,
+};
+
+use crate::entities::{
+    one_time_token::{self, ActiveModel as OneTimeTokenActiveModel, Entity as OneTimeToken},
+    sea_orm_active_enums::TokenPurpose,
+};
+use crate::utils::tokens::{generate_token, hash_token};
+
+/// Issues a single-use token for `user_id` and returns it in plain text. Any
+/// outstanding token of the same purpose is discarded, so only the most
+/// recently sent link works.
+pub async fn issue_one_time_token<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    purpose: TokenPurpose,
+    lifetime: Duration,
+) -> Result<String, DbErr> {
+    OneTimeToken::delete_many()
+        .filter(one_time_token::Column::UserId.eq(user_id))
+        .filter(one_time_token::Column::Purpose.eq(purpose))
+        .filter(one_time_token::Column::UsedAt.is_null())
+        .exec(db)
+        .await?;
+
+    let token = generate_token();
+    OneTimeTokenActiveModel {
+        user_id: Set(user_id),
+        purpose: Set(purpose),
+        token_hash: Set(hash_token(&token)),
+        expires_at: Set(Utc::now() + lifetime),
+        used_at: Set(None),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(token)
+}
+
+/// Marks `token` as used and returns it, or `None` when the token is unknown,
+/// issued for another purpose, already used or expired. Call inside a database
+/// transaction together with the action the token authorizes.
+pub async fn consume_one_time_token<C: ConnectionTrait>(
+    db: &C,
+    token: &str,
+    purpose: TokenPurpose,
+) -> Result<Option<one_time_token::Model>, DbErr> {
+    let Some(found) = OneTimeToken::find()
+        .filter(one_time_token::Column::TokenHash.eq(hash_token(token)))
+        .filter(one_time_token::Column::Purpose.eq(purpose))
+        .filter(one_time_token::Column::UsedAt.is_null())
+        .filter(one_time_token::Column::ExpiresAt.gt(Utc::now()))
+        .lock_exclusive()
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let mut used: OneTimeTokenActiveModel = found.into();
+    used.used_at = Set(Some(Utc::now()));
+    Ok(Some(used.update(db).await?))
+}
@@ -0,0 +1,147 @@
+use std::{env, io};
+
+use argon2::{
+    Algorithm, Argon2, Params, PasswordHasher, PasswordVerifier, Version,
+    password_hash::PasswordHash,
+    password_hash::{SaltString, rand_core::OsRng},
+};
+use serde_json::json;
+
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+/// Upper bound on password length, so hashing cost stays bounded.
+const MAX_LENGTH: usize = 128;
+
+/// Password requirements and Argon2 cost parameters, read from the
+/// environment at startup.
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// How many of lowercase, uppercase, digits and symbols must appear.
+    pub min_character_classes: usize,
+    /// Reject passwords from the bundled list of common passwords.
+    pub reject_common: bool,
+    pub argon2_params: Params,
+}
+
+impl PasswordPolicy {
+    pub fn from_env() -> io::Result<Self> {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let argon2_params = Params::new(
+            var("ARGON2_MEMORY_KIB", Params::DEFAULT_M_COST),
+            var("ARGON2_ITERATIONS", Params::DEFAULT_T_COST),
+            var("ARGON2_PARALLELISM", Params::DEFAULT_P_COST),
+            None,
+        )
+        .map_err(|e| io::Error::other(format!("❌ Invalid Argon2 parameters: {}", e)))?;
+
+        Ok(Self {
+            min_length: var("PASSWORD_MIN_LENGTH", 10),
+            min_character_classes: var("PASSWORD_MIN_CHARACTER_CLASSES", 2).min(4),
+            reject_common: var("PASSWORD_REJECT_COMMON", true),
+            argon2_params,
+        })
+    }
+
+    /// Checks `password` against the policy, returning a 422 that explains the
+    /// first unmet requirement.
+    pub fn validate(&self, password: &str) -> Result<(), actix_web::Error> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(weak_password(&format!(
+                "Password must be at least {} characters long",
+                self.min_length
+            )));
+        }
+        if length > MAX_LENGTH {
+            return Err(weak_password(&format!(
+                "Password must be at most {} characters long",
+                MAX_LENGTH
+            )));
+        }
+
+        let classes = [
+            password.chars().any(|c| c.is_lowercase()),
+            password.chars().any(|c| c.is_uppercase()),
+            password.chars().any(|c| c.is_numeric()),
+            password.chars().any(|c| !c.is_alphanumeric()),
+        ]
+        .into_iter()
+        .filter(|present| *present)
+        .count();
+        if classes < self.min_character_classes {
+            return Err(weak_password(&format!(
+                "Password must mix at least {} of lowercase letters, uppercase letters, digits and symbols",
+                self.min_character_classes
+            )));
+        }
+
+        if self.reject_common && is_common(password) {
+            return Err(weak_password(
+                "Password is too common, please choose another one",
+            ));
+        }
+        Ok(())
+    }
+
+    fn hasher(&self) -> Argon2<'static> {
+        Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            self.argon2_params.clone(),
+        )
+    }
+}
+
+pub fn hash_password(policy: &PasswordPolicy, password: &str) -> Result<String, actix_web::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(policy
+        .hasher()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Hash error: {}", e)))?
+        .to_string())
+}
+
+/// Returns whether `password` matches the stored Argon2 `hash`. The cost
+/// parameters are taken from the hash itself.
+pub fn verify_password(password: &str, hash: &str) -> Result<bool, actix_web::Error> {
+    let parsed_hash = PasswordHash::new(hash)
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Hash error: {}", e)))?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok())
+}
+
+/// Whether `hash` was produced with a different algorithm, version or cost
+/// parameters than the policy currently asks for.
+pub fn needs_rehash(policy: &PasswordPolicy, hash: &str) -> bool {
+    let Ok(parsed_hash) = PasswordHash::new(hash) else {
+        return true;
+    };
+    let Ok(params) = Params::try_from(&parsed_hash) else {
+        return true;
+    };
+    let current = &policy.argon2_params;
+
+    parsed_hash.algorithm != Algorithm::Argon2id.ident()
+        || parsed_hash.version != Some(Version::V0x13.into())
+        || params.m_cost() != current.m_cost()
+        || params.t_cost() != current.t_cost()
+        || params.p_cost() != current.p_cost()
+}
+
+fn is_common(password: &str) -> bool {
+    let password = password.to_lowercase();
+    COMMON_PASSWORDS
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .any(|common| common.eq_ignore_ascii_case(&password))
+}
+
+fn weak_password(message: &str) -> actix_web::Error {
+    actix_web::error::ErrorUnprocessableEntity(json!({"status": "error", "message": message}))
+}
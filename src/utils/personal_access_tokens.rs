@@ -0,0 +1,142 @@
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, Set,
+    sea_query::Expr,
+};
+
+use crate::entities::{
+    personal_access_token::{
+        self, ActiveModel as PersonalAccessTokenActiveModel, Entity as PersonalAccessToken,
+        Model as PersonalAccessTokenModel,
+    },
+    user::Entity as User,
+};
+use crate::utils::responses::{forbidden, unauthorized};
+use crate::utils::tokens::{generate_token, hash_token};
+
+/// Prefix that tells personal access tokens apart from JWTs.
+pub const TOKEN_PREFIX: &str = "spat_";
+
+/// Scopes a personal access token can be granted. `<resource>:write` also
+/// allows reading that resource.
+pub const SCOPES: &[&str] = &[
+    "accounts:read",
+    "accounts:write",
+    "categories:read",
+    "categories:write",
+    "transactions:read",
+    "transactions:write",
+    "balances:read",
+    "balances:write",
+    "exchange_rates:read",
+    "exchange_rates:write",
+];
+
+/// `last_used_at` is refreshed at most this often to avoid a write per request.
+const LAST_USED_RESOLUTION: Duration = Duration::minutes(1);
+
+/// Scopes granted to the token that authenticated the current request.
+/// Only present for requests made with a personal access token.
+#[derive(Clone, Debug)]
+pub struct TokenScopes(pub Vec<String>);
+
+impl TokenScopes {
+    /// Whether the token may perform `access` (`read` or `write`) on `resource`.
+    pub fn allows(&self, resource: &str, access: &str) -> bool {
+        self.0.iter().any(|scope| {
+            scope == &format!("{}:{}", resource, access) || scope == &format!("{}:write", resource)
+        })
+    }
+}
+
+/// Creates a token for `user_id` and returns the plaintext alongside the stored
+/// row. The plaintext is never persisted.
+pub async fn create_personal_access_token<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    name: &str,
+    scopes: &[String],
+    expires_at: Option<chrono::DateTime<Utc>>,
+) -> Result<(String, PersonalAccessTokenModel), DbErr> {
+    let token = format!("{}{}", TOKEN_PREFIX, generate_token());
+
+    let model = PersonalAccessTokenActiveModel {
+        user_id: Set(user_id),
+        name: Set(name.to_string()),
+        token_prefix: Set(token.chars().take(12).collect()),
+        token_hash: Set(hash_token(&token)),
+        scopes: Set(scopes.join(" ")),
+        expires_at: Set(expires_at),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok((token, model))
+}
+
+/// Resolves a personal access token to its owner and scopes, rejecting revoked,
+/// expired or disabled-account tokens.
+pub async fn authenticate_personal_access_token<C: ConnectionTrait>(
+    db: &C,
+    token: &str,
+) -> Result<(i32, TokenScopes), actix_web::Error> {
+    let (record, user) = PersonalAccessToken::find()
+        .filter(personal_access_token::Column::TokenHash.eq(hash_token(token)))
+        .find_also_related(User)
+        .one(db)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| unauthorized("Invalid access token"))?;
+
+    if record.revoked_at.is_some() {
+        return Err(unauthorized("Access token has been revoked"));
+    }
+    if record
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(unauthorized("Access token has expired"));
+    }
+    let user = user.ok_or_else(|| unauthorized("Invalid access token"))?;
+    if user.disabled_at.is_some() {
+        return Err(forbidden("Account is disabled"));
+    }
+
+    let now = Utc::now();
+    if record
+        .last_used_at
+        .is_none_or(|last_used_at| now - last_used_at >= LAST_USED_RESOLUTION)
+    {
+        PersonalAccessToken::update_many()
+            .col_expr(
+                personal_access_token::Column::LastUsedAt,
+                Expr::value(Some(now)),
+            )
+            .filter(personal_access_token::Column::Id.eq(record.id))
+            .exec(db)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?;
+    }
+
+    let scopes = record.scopes.split_whitespace().map(String::from).collect();
+    Ok((user.id, TokenScopes(scopes)))
+}
+
+/// Revokes every personal access token of `user_id`, e.g. after a password
+/// change, so a leaked token does not outlive the password it was made with.
+pub async fn revoke_personal_access_tokens<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+) -> Result<(), DbErr> {
+    PersonalAccessToken::update_many()
+        .col_expr(personal_access_token::Column::RevokedAt, Utc::now().into())
+        .filter(personal_access_token::Column::UserId.eq(user_id))
+        .filter(personal_access_token::Column::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(())
+}
@@ -6,4 +6,11 @@ pub fn unauthorized(message: &str) -> Error {
         "status": "error",
         "message": message
     }))
-}
\ No newline at end of file
+}
+
+pub fn forbidden(message: &str) -> Error {
+    actix_web::error::ErrorForbidden(json!({
+        "status": "error",
+        "message": message
+    }))
+}
@@ -0,0 +1,248 @@
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::{
+    refresh_token::{self, ActiveModel as RefreshTokenActiveModel, Entity as RefreshToken},
+    session::{self, ActiveModel as SessionActiveModel, Entity as Session},
+    user::{Entity as User, Model as UserModel},
+};
+use crate::utils::{
+    auth::ClientInfo,
+    jwt::issue_access_token,
+    jwt_keys::KeyRing,
+    responses::{forbidden, unauthorized},
+    tokens::{generate_token, hash_token},
+};
+
+pub const REFRESH_TOKEN_LIFETIME: Duration = Duration::days(30);
+
+/// `last_seen_at` is refreshed at most this often to avoid a write per request.
+const LAST_SEEN_RESOLUTION: Duration = Duration::minutes(1);
+
+pub struct IssuedTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+/// Opens a new session for `user` and issues its first token pair.
+pub async fn start_session<C: ConnectionTrait>(
+    db: &C,
+    jwt_keys: &KeyRing,
+    user: &UserModel,
+    client: &ClientInfo,
+) -> Result<IssuedTokens, actix_web::Error> {
+    let jti = Uuid::new_v4().to_string();
+    let session = SessionActiveModel {
+        id: Set(Uuid::new_v4().to_string()),
+        user_id: Set(user.id),
+        created_at: Set(Utc::now()),
+        revoked_at: Set(None),
+        user_agent: Set(client.user_agent.clone()),
+        ip: Set(Some(client.ip.clone())),
+        last_seen_at: Set(Some(Utc::now())),
+        access_token_jti: Set(Some(jti.clone())),
+    }
+    .insert(db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    let refresh_token = insert_refresh_token(db, &session.id).await?;
+    let access_token = issue_access_token(jwt_keys, user, &session.id, &jti)?;
+
+    Ok(IssuedTokens {
+        access_token,
+        refresh_token,
+    })
+}
+
+/// Exchanges a refresh token for a new token pair. Every refresh token is
+/// single-use: presenting one that was already rotated is treated as theft
+/// and revokes the whole session (token family).
+pub async fn rotate_refresh_token(
+    db: &DatabaseConnection,
+    jwt_keys: &KeyRing,
+    token: &str,
+    client: &ClientInfo,
+) -> Result<IssuedTokens, actix_web::Error> {
+    let txn = db.begin().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let (current, session) = RefreshToken::find()
+        .filter(refresh_token::Column::TokenHash.eq(hash_token(token)))
+        .find_also_related(Session)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| unauthorized("Invalid refresh token"))?;
+    let session = session.ok_or_else(|| unauthorized("Invalid refresh token"))?;
+
+    if session.revoked_at.is_some() {
+        return Err(unauthorized("Session has been revoked"));
+    }
+
+    if current.used_at.is_some() {
+        revoke_session(&txn, &session.id).await.map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+        txn.commit().await.map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+        return Err(unauthorized(
+            "Refresh token reuse detected; session revoked",
+        ));
+    }
+
+    if current.expires_at < Utc::now() {
+        return Err(unauthorized("Refresh token has expired"));
+    }
+
+    let user = User::find_by_id(session.user_id)
+        .one(&txn)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| unauthorized("Invalid refresh token"))?;
+    if user.disabled_at.is_some() {
+        return Err(forbidden("Account is disabled"));
+    }
+
+    let mut used: RefreshTokenActiveModel = current.into();
+    used.used_at = Set(Some(Utc::now()));
+    used.update(&txn).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let jti = Uuid::new_v4().to_string();
+    let mut refreshed: SessionActiveModel = session.into();
+    refreshed.user_agent = Set(client.user_agent.clone());
+    refreshed.ip = Set(Some(client.ip.clone()));
+    refreshed.last_seen_at = Set(Some(Utc::now()));
+    refreshed.access_token_jti = Set(Some(jti.clone()));
+    let session = refreshed.update(&txn).await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let refresh_token = insert_refresh_token(&txn, &session.id).await?;
+    let access_token = issue_access_token(jwt_keys, &user, &session.id, &jti)?;
+
+    txn.commit().await.map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+    })?;
+
+    Ok(IssuedTokens {
+        access_token,
+        refresh_token,
+    })
+}
+
+/// Revokes the session owning `token`, if any. Unknown tokens are ignored so
+/// signing out is idempotent.
+pub async fn revoke_session_by_refresh_token(
+    db: &DatabaseConnection,
+    token: &str,
+) -> Result<(), DbErr> {
+    let current = RefreshToken::find()
+        .filter(refresh_token::Column::TokenHash.eq(hash_token(token)))
+        .one(db)
+        .await?;
+
+    if let Some(current) = current {
+        revoke_session(db, &current.session_id).await?;
+    }
+    Ok(())
+}
+
+pub async fn revoke_session<C: ConnectionTrait>(db: &C, session_id: &str) -> Result<(), DbErr> {
+    Session::update_many()
+        .col_expr(session::Column::RevokedAt, Utc::now().into())
+        .filter(session::Column::Id.eq(session_id))
+        .filter(session::Column::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Revokes every open session of `user_id`, e.g. when the account is disabled.
+pub async fn revoke_user_sessions<C: ConnectionTrait>(db: &C, user_id: i32) -> Result<(), DbErr> {
+    Session::update_many()
+        .col_expr(session::Column::RevokedAt, Utc::now().into())
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Revokes every open session of `user_id` except `keep_session_id`, e.g.
+/// after a password change made from that session.
+pub async fn revoke_other_sessions<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    keep_session_id: &str,
+) -> Result<(), DbErr> {
+    Session::update_many()
+        .col_expr(session::Column::RevokedAt, Utc::now().into())
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::Id.ne(keep_session_id))
+        .filter(session::Column::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Returns whether `session_id` is still active and `jti` is its latest
+/// access token, recording the session as seen. Access tokens issued before a
+/// refresh stop working once the refresh has rotated the `jti`.
+pub async fn touch_session<C: ConnectionTrait>(
+    db: &C,
+    session_id: &str,
+    jti: &str,
+) -> Result<bool, DbErr> {
+    let Some(session) = Session::find_by_id(session_id)
+        .filter(session::Column::RevokedAt.is_null())
+        .filter(session::Column::AccessTokenJti.eq(jti))
+        .one(db)
+        .await?
+    else {
+        return Ok(false);
+    };
+
+    let now = Utc::now();
+    if session
+        .last_seen_at
+        .is_none_or(|last_seen_at| now - last_seen_at >= LAST_SEEN_RESOLUTION)
+    {
+        Session::update_many()
+            .col_expr(session::Column::LastSeenAt, now.into())
+            .filter(session::Column::Id.eq(session_id))
+            .exec(db)
+            .await?;
+    }
+    Ok(true)
+}
+
+async fn insert_refresh_token<C: ConnectionTrait>(
+    db: &C,
+    session_id: &str,
+) -> Result<String, actix_web::Error> {
+    let token = generate_token();
+
+    RefreshTokenActiveModel {
+        session_id: Set(session_id.to_string()),
+        token_hash: Set(hash_token(&token)),
+        expires_at: Set(Utc::now() + REFRESH_TOKEN_LIFETIME),
+        used_at: Set(None),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(token)
+}
@@ -0,0 +1,140 @@
+use chrono::Utc;
+use rand::{Rng, rngs::OsRng};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect, Set,
+};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::entities::{
+    recovery_code::{self, ActiveModel as RecoveryCodeActiveModel, Entity as RecoveryCode},
+    user::{ActiveModel as UserActiveModel, Model as UserModel},
+};
+use crate::utils::tokens::hash_token;
+
+const ISSUER: &str = "Spenser";
+const DIGITS: usize = 6;
+const STEP_SECONDS: u64 = 30;
+/// Accepted clock drift, in steps, on either side of the current one.
+const SKEW_STEPS: i64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Generates a new 160-bit shared secret, base32-encoded.
+pub fn generate_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+/// Builds the RFC 6238 generator (SHA-1, 6 digits, 30 s) for a stored secret.
+pub fn build_totp(secret: &str, account_name: &str) -> Result<TOTP, actix_web::Error> {
+    let bytes = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("TOTP error: {}", e)))?;
+
+    TOTP::new(
+        Algorithm::SHA1,
+        DIGITS,
+        SKEW_STEPS as u8,
+        STEP_SECONDS,
+        bytes,
+        Some(ISSUER.to_string()),
+        account_name.to_string(),
+    )
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("TOTP error: {}", e)))
+}
+
+/// Checks `code` against the current time step and its neighbours. Returns the
+/// matching step, which must be newer than `last_step` so that a code can
+/// never be replayed.
+pub fn verify_code(totp: &TOTP, code: &str, last_step: Option<i64>) -> Option<i64> {
+    let current_step = Utc::now().timestamp() / STEP_SECONDS as i64;
+
+    (current_step - SKEW_STEPS..=current_step + SKEW_STEPS)
+        .filter(|step| last_step.is_none_or(|last| *step > last))
+        .find(|step| totp.generate(*step as u64 * STEP_SECONDS) == code.trim())
+}
+
+/// Generates a fresh batch of recovery codes, formatted `XXXX-XXXX-XXXX-XXXX`.
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let chars: Vec<char> = (0..16)
+                .map(|_| {
+                    RECOVERY_CODE_ALPHABET[OsRng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char
+                })
+                .collect();
+            chars
+                .chunks(4)
+                .map(|chunk| chunk.iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .collect()
+}
+
+/// Hashes a recovery code, ignoring case and dashes as typed by the user.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    hash_token(&normalized)
+}
+
+/// Verifies the second factor of `user`: either a TOTP `code` or an unused
+/// `recovery_code`, which is consumed. Run inside a transaction that has
+/// locked the user row, so concurrent attempts cannot replay the same code.
+pub async fn verify_second_factor<C: ConnectionTrait>(
+    db: &C,
+    user: &UserModel,
+    code: Option<&str>,
+    recovery_code: Option<&str>,
+) -> Result<bool, actix_web::Error> {
+    let Some(secret) = user.totp_secret.as_deref() else {
+        return Ok(false);
+    };
+
+    if let Some(code) = code {
+        let totp = build_totp(secret, &user.email)?;
+        let Some(step) = verify_code(&totp, code, user.totp_last_step) else {
+            return Ok(false);
+        };
+
+        UserActiveModel {
+            id: Set(user.id),
+            totp_last_step: Set(Some(step)),
+            ..Default::default()
+        }
+        .update(db)
+        .await
+        .map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+        return Ok(true);
+    }
+
+    if let Some(recovery_code) = recovery_code {
+        let Some(found) = RecoveryCode::find()
+            .filter(recovery_code::Column::UserId.eq(user.id))
+            .filter(recovery_code::Column::CodeHash.eq(hash_recovery_code(recovery_code)))
+            .filter(recovery_code::Column::UsedAt.is_null())
+            .lock_exclusive()
+            .one(db)
+            .await
+            .map_err(|e| {
+                actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+            })?
+        else {
+            return Ok(false);
+        };
+
+        let mut used: RecoveryCodeActiveModel = found.into();
+        used.used_at = Set(Some(Utc::now()));
+        used.update(db).await.map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Database error: {}", e))
+        })?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
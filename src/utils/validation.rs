@@ -0,0 +1,38 @@
+use serde_json::json;
+
+/// Longest email address SMTP can deliver to.
+const EMAIL_MAX_LENGTH: usize = 254;
+
+/// A `400 Bad Request` carrying `message` in the usual error body.
+pub fn invalid_request(message: &str) -> actix_web::Error {
+    actix_web::error::ErrorBadRequest(json!({"status": "error", "message": message}))
+}
+
+/// Trims `name` and checks that it fits the 255-character name columns.
+pub fn validate_name(name: &str) -> Result<&str, actix_web::Error> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 255 {
+        return Err(invalid_request("Name must be between 1 and 255 characters"));
+    }
+    Ok(name)
+}
+
+/// Checks that `email` looks like a deliverable address: one `@` with a
+/// non-empty local part and a dotted domain, and no whitespace.
+pub fn validate_email(email: &str) -> Result<(), actix_web::Error> {
+    let valid = email.len() <= EMAIL_MAX_LENGTH
+        && !email.chars().any(char::is_whitespace)
+        && email.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && domain.split('.').all(|label| !label.is_empty())
+        });
+    if !valid {
+        return Err(actix_web::error::ErrorUnprocessableEntity(json!({
+            "status": "error",
+            "message": "Email address is invalid."
+        })));
+    }
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, Set,
+};
+
+use crate::entities::{
+    sea_orm_active_enums::WorkspaceRole,
+    workspace::{
+        ActiveModel as WorkspaceActiveModel, Entity as Workspace, Model as WorkspaceModel,
+    },
+    workspace_member::{
+        self, ActiveModel as WorkspaceMemberActiveModel, Entity as WorkspaceMember,
+        Model as WorkspaceMemberModel,
+    },
+};
+use crate::utils::responses::forbidden;
+
+/// Request header that selects the workspace for `/api` resources.
+pub const WORKSPACE_HEADER: &str = "X-Workspace-Id";
+/// Name of the workspace every new account starts with.
+pub const PERSONAL_WORKSPACE_NAME: &str = "Personal";
+/// Base currency of workspaces created without one.
+pub const DEFAULT_BASE_CURRENCY: &str = "USD";
+
+/// What a request may do in the workspace it acts in, resolved from the
+/// caller's membership or from an access grant.
+#[derive(Clone, Debug)]
+pub struct WorkspaceAccess {
+    pub workspace_id: i32,
+    pub user_id: i32,
+    pub can_write: bool,
+    /// Categories an access grant is limited to; `None` means all of them.
+    pub category_ids: Option<Vec<i32>>,
+}
+
+impl WorkspaceAccess {
+    pub fn from_membership(member: &WorkspaceMemberModel) -> Self {
+        Self {
+            workspace_id: member.workspace_id,
+            user_id: member.user_id,
+            can_write: can_write(member.role),
+            category_ids: None,
+        }
+    }
+
+    pub fn allows_category(&self, category_id: i32) -> bool {
+        self.category_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&category_id))
+    }
+
+    /// Rejects access grants limited to some categories, for features that
+    /// would reveal the rest of the workspace.
+    pub fn require_whole_workspace(&self, message: &str) -> Result<(), actix_web::Error> {
+        if self.category_ids.is_some() {
+            return Err(forbidden(message));
+        }
+        Ok(())
+    }
+}
+
+/// Creates a workspace with `user_id` as its owner.
+pub async fn create_owned_workspace<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    name: &str,
+    base_currency: &str,
+) -> Result<WorkspaceModel, DbErr> {
+    let workspace = WorkspaceActiveModel {
+        name: Set(name.to_string()),
+        balance: Set(0),
+        base_currency: Set(base_currency.to_string()),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    WorkspaceMemberActiveModel {
+        workspace_id: Set(workspace.id),
+        user_id: Set(user_id),
+        role: Set(WorkspaceRole::Owner),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(workspace)
+}
+
+/// Membership of `user_id` in `workspace_id`. Without a workspace id, the
+/// user's oldest membership is used, which is their personal workspace unless
+/// they left it.
+pub async fn find_membership<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    workspace_id: Option<i32>,
+) -> Result<Option<WorkspaceMemberModel>, DbErr> {
+    let mut query = WorkspaceMember::find().filter(workspace_member::Column::UserId.eq(user_id));
+    if let Some(workspace_id) = workspace_id {
+        query = query.filter(workspace_member::Column::WorkspaceId.eq(workspace_id));
+    }
+    query
+        .order_by_asc(workspace_member::Column::Id)
+        .one(db)
+        .await
+}
+
+/// Owners and editors may change categories and transactions; viewers only
+/// read them.
+pub fn can_write(role: WorkspaceRole) -> bool {
+    matches!(role, WorkspaceRole::Owner | WorkspaceRole::Editor)
+}
+
+/// Currency the workspace reports in, which its balance is kept in.
+pub async fn base_currency<C: ConnectionTrait>(db: &C, workspace_id: i32) -> Result<String, DbErr> {
+    Workspace::find_by_id(workspace_id)
+        .one(db)
+        .await?
+        .map(|workspace| workspace.base_currency)
+        .ok_or_else(|| DbErr::RecordNotFound("Workspace not found".to_string()))
+}
+
+pub async fn count_owners<C: ConnectionTrait>(db: &C, workspace_id: i32) -> Result<u64, DbErr> {
+    WorkspaceMember::find()
+        .filter(workspace_member::Column::WorkspaceId.eq(workspace_id))
+        .filter(workspace_member::Column::Role.eq(WorkspaceRole::Owner))
+        .count(db)
+        .await
+}